@@ -0,0 +1,287 @@
+//! Constants used in rCore
+
+/// user app's stack size
+pub const USER_STACK_SIZE: usize = 4096 * 2;
+/// size in bytes of the unmapped-in-spirit guard region reserved just below
+/// each app's user stack; see [`crate::loader::user_stack_guard_range`]
+pub const USER_STACK_GUARD_SIZE: usize = 4096;
+/// kernel stack size
+pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
+/// size in bytes of the canary-filled guard region reserved just below each
+/// app's kernel stack; see [`crate::loader::kernel_stack_canary_intact`]
+pub const KERNEL_STACK_GUARD_SIZE: usize = 256;
+/// kernel heap size
+pub const KERNEL_HEAP_SIZE: usize = 0x3_0000;
+/// the max number of apps that can be loaded at once
+pub const MAX_APP_NUM: usize = 16;
+/// the max syscall id tracked in the externally-visible syscall histogram
+pub const MAX_SYSCALL_NUM: usize = 500;
+/// base address of applications
+pub const APP_BASE_ADDRESS: usize = 0x80400000;
+/// the maximum size of an application
+pub const APP_SIZE_LIMIT: usize = 0x20000;
+/// the frequency of the platform clock, used to convert `mtime` ticks into wall time
+pub const CLOCK_FREQ: usize = 12500000;
+/// the timebase userspace should divide a [`crate::timer::get_cycles`]
+/// delta by to get seconds
+///
+/// This kernel has no way to read the core's real clock rate separately
+/// from the platform timer QEMU exposes, so this reuses [`CLOCK_FREQ`] as
+/// a stand-in; on real hardware the `cycle` CSR and the platform timer are
+/// usually two independent counters running at two different rates.
+pub const CYCLE_FREQ: usize = CLOCK_FREQ;
+/// number of timer interrupts triggered per second, i.e. the length of a time slice
+pub const TICKS_PER_SEC: usize = 100;
+/// the maximum number of distinct `mmap` regions a single task may hold open
+/// at once
+pub const MAX_MMAP_AREAS: usize = 16;
+/// the number of most-recent syscall ids a task's ring buffer remembers
+pub const RECENT_SYSCALL_LOG_LEN: usize = 8;
+/// the maximum number of mutexes a single process may have open at once
+pub const MAX_MUTEX_NUM: usize = 8;
+/// the maximum number of semaphores a single process may have open at once
+pub const MAX_SEM_NUM: usize = 8;
+/// the maximum number of condition variables a single process may have open
+/// at once
+pub const MAX_CONDVAR_NUM: usize = 8;
+/// the size in bytes of a pipe's kernel ring buffer
+pub const PIPE_BUF_LEN: usize = 256;
+/// the maximum number of pipes open across the whole kernel at once; unlike
+/// mutexes/semaphores/condvars, pipes are a global (not per-process) kernel
+/// object table, since a pipe must go on being shared correctly by a parent
+/// and child that no longer share a `memory_slot` after `fork`
+pub const MAX_PIPE_NUM: usize = 16;
+/// the maximum number of file descriptors a single task may hold open at
+/// once, including the pre-installed stdin/stdout/stderr slots
+pub const MAX_FD_NUM: usize = 16;
+/// the maximum number of named files the in-kernel filesystem can hold at
+/// once; see [`crate::fs`]
+pub const MAX_FILES: usize = 16;
+/// the maximum size in bytes of a single file
+pub const MAX_FILE_SIZE: usize = 4096;
+/// the maximum length in bytes of a file's path
+pub const MAX_PATH_LEN: usize = 32;
+/// the number of distinct signal numbers this kernel tracks, indexed
+/// directly by signal number; real Linux signal numbers go up to 31
+pub const MAX_SIG_NUM: usize = 32;
+/// the maximum number of `IoVec` entries a single `sys_writev`/`sys_readv`
+/// call may pass, the same bound [`MAX_FD_NUM`]
+/// puts on a single `sys_poll` call's `fds` array — real Linux's own
+/// `IOV_MAX` is far larger (1024), but this teaching kernel has never
+/// needed more than a handful of scattered buffers in one call
+pub const MAX_IOV_NUM: usize = 16;
+/// the number of hart slots the task manager's per-CPU state (e.g.
+/// [`crate::task::TaskManager::get_current_task`]) is sized for; see
+/// [`crate::hart`]
+pub const MAX_HARTS: usize = 4;
+/// the maximum number of `argv` entries `sys_exec` will copy out of the
+/// caller's old address space before replacing it; an `argv` array with
+/// more non-null entries than this makes the call fail, the same way a
+/// real `execve` fails with `E2BIG` for an oversized argument list
+pub const MAX_EXEC_ARGS: usize = 8;
+/// the maximum length in bytes of a single `sys_exec` argument string,
+/// nul included; longer truncates the same way [`MAX_PATH_LEN`] does for
+/// a path
+pub const MAX_ARG_LEN: usize = 64;
+/// the maximum number of distinct `uaddr`s that may have a futex wait queue
+/// open on them at once, kernel-wide; see [`crate::task::Futex`]
+pub const MAX_FUTEX_NUM: usize = 16;
+/// the size in bytes of the fixed stack buffer a `/proc/<pid>/stat` read
+/// is formatted into; see `crate::syscall::fs::proc_stat_line`. Generous
+/// enough for every field that line reports (a pid, a status word, and a
+/// few `usize` counters) with room to spare, the same "fixed capacity,
+/// not exactly sized" approach [`MAX_PATH_LEN`] and friends already take
+pub const PROC_STAT_LINE_LEN: usize = 128;
+/// the most ops a single `sys_batch` call will execute; an oversized batch
+/// fails the same way an oversized `sys_exec` argument list does (see
+/// [`MAX_EXEC_ARGS`]) rather than looping over an unbounded caller-supplied
+/// count
+pub const MAX_BATCH_OPS: usize = 32;
+/// the maximum length in bytes of a task's `sys_prctl`-settable name,
+/// real Linux's `PR_SET_NAME` limit (15 bytes plus a nul this kernel
+/// doesn't store, since [`TaskControlBlock::name_len`](crate::task::TaskControlBlock::name_len)
+/// tracks the length directly instead)
+pub const MAX_TASK_NAME_LEN: usize = 16;
+/// how long (in milliseconds) a `Ready` task may go without being
+/// scheduled before its stride is given an anti-starvation aging boost;
+/// see [`crate::task::TaskControlBlock::ready_since_ms`]
+pub const PRIORITY_AGING_THRESHOLD_MS: usize = 1000;
+/// the stride aging boost applied to a task that has been starved past
+/// [`PRIORITY_AGING_THRESHOLD_MS`] — large enough to jump ahead of a
+/// `DEFAULT_PRIORITY` task's usual stride increment for a while, but not
+/// so large it can itself start starving everyone else
+pub const PRIORITY_AGING_BOOST: usize = 50_000;
+/// how many dispatches in a row a task keeps earning
+/// [`IO_WAKE_BOOST_STRIDE_CREDIT`] after waking from a block, before the
+/// boost decays away on its own; see
+/// [`crate::task::TaskControlBlock::io_wake_boost`]
+pub const IO_WAKE_BOOST_SLICES: usize = 3;
+/// the stride credit an interactive task (one that just woke from a
+/// block rather than being preempted) gets applied on top of
+/// [`PRIORITY_AGING_BOOST`]'s anti-starvation credit, for as long as
+/// [`IO_WAKE_BOOST_SLICES`] hasn't decayed away; smaller than
+/// `PRIORITY_AGING_BOOST` since this is a short-lived responsiveness
+/// nudge rather than a starvation fix
+pub const IO_WAKE_BOOST_STRIDE_CREDIT: usize = 30_000;
+/// how many consecutive `sys_yield`s a task may make — without an
+/// intervening block or a timer preemption, either of which would mean it
+/// actually did something with its CPU time — before the livelock
+/// watchdog treats it as a tight yield-spinner and demotes its priority
+/// by [`LIVELOCK_DEMOTE_STEP`]; see
+/// [`crate::task::TaskControlBlock::yield_streak`]
+pub const LIVELOCK_YIELD_THRESHOLD: usize = 1000;
+/// how much priority the livelock watchdog takes away each time
+/// [`LIVELOCK_YIELD_THRESHOLD`] is crossed; repeated demotions stack
+/// (clamped at the documented `[2, MAX_PRIO]` floor by
+/// [`crate::task::TaskManager::set_current_priority`]'s own clamp logic,
+/// applied inline here rather than through that method since this isn't a
+/// `sys_set_priority` call), so a spinner that keeps right on spinning
+/// keeps losing ground rather than being demoted once and left alone
+pub const LIVELOCK_DEMOTE_STEP: isize = 4;
+/// the maximum number of System-V-style shared memory segments open across
+/// the whole kernel at once; kernel-wide rather than per-process, for the
+/// same reason as [`MAX_PIPE_NUM`] — two unrelated processes must be able to
+/// find the same segment by key
+pub const MAX_SHM_NUM: usize = 8;
+/// the fixed size, in bytes, of every shared memory segment's backing
+/// storage; see [`crate::task::shmget_current`]
+pub const SHM_SEGMENT_SIZE: usize = 4096;
+/// the widest byte range an app's initial user stack pointer may be pushed
+/// down from the top of its reserved [`crate::loader::UserStack`] by ASLR
+/// (see [`crate::loader::aslr_stack_top`]); `0` disables stack
+/// randomization entirely, for test harnesses that need a deterministic
+/// stack pointer to compare against
+pub const ASLR_STACK_RANGE: usize = 0x400;
+/// whether `sys_sbrk` growth skips eagerly zeroing the newly claimed heap
+/// bytes
+///
+/// This kernel has no page-table-backed demand paging (see
+/// [`crate::mm`]'s module doc), so there is no fault path to defer the
+/// zeroing to in the first place — flipping this to `true` just means a
+/// growing heap trusts the app to overwrite what it asked for before
+/// reading it, in exchange for skipping the zero fill. `false` is the only
+/// safe default: a `memory_slot` gets reused by a later, unrelated process
+/// once its previous owner exits (see [`crate::task::TaskManager::spawn_current`]),
+/// so an un-zeroed heap growth can otherwise read that previous process's
+/// leftover bytes.
+pub const LAZY_HEAP_ZEROING: bool = false;
+/// whether `sys_exit` prints a one-line summary of the exiting task's total
+/// run time and syscall count before it's handed off to
+/// [`crate::task::exit_current_and_run_next`]
+///
+/// Off by default so ordinary runs stay quiet; autograding harnesses that
+/// want a machine-parseable trailer per task can flip this at build time.
+pub const VERBOSE_EXIT_STATS: bool = false;
+/// whether every task exit — whether from `sys_exit` or killed outright by
+/// a fault in [`crate::trap::trap_handler`] — prints a machine-parseable
+/// `[EXIT] pid=<p> code=<c>` marker line, from
+/// [`crate::task::exit_current_and_run_next`]
+///
+/// Off by default for the same reason as [`VERBOSE_EXIT_STATS`]: a grading
+/// harness that wants to attribute pass/fail per app by scraping the SBI
+/// console output can flip this at build time, without every ordinary run
+/// growing an extra line per exit.
+pub const EXIT_MARKER_FOR_GRADER: bool = false;
+/// whether [`crate::trap::trap_handler`] records interrupt-to-switch
+/// latency (in cycles, via [`crate::timer::get_cycles`]) for every timer
+/// interrupt, queryable through `sys_irqstats`
+///
+/// Off by default: reading the cycle counter twice per tick is cheap, but
+/// it's still overhead a normal run has no use for, so like
+/// [`VERBOSE_EXIT_STATS`]/[`EXIT_MARKER_FOR_GRADER`] it's a build-time flag
+/// rather than something always on.
+pub const IRQ_LATENCY_STATS: bool = false;
+/// the scheduler disciplines [`crate::task::TaskManager::find_next_task`]
+/// can be compiled to use; see [`SCHED_POLICY`]
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SchedPolicy {
+    /// ignore stride/priority entirely; always hands a hart to whichever
+    /// `Ready` task on its queue comes next after its own previously
+    /// dispatched slot, cycling through evenly
+    RoundRobin,
+    /// this kernel's stock scheduler: stride scheduling with anti-
+    /// starvation aging and an interactivity boost; see
+    /// `crate::task::TaskManager::find_next_task`'s own doc comment
+    Stride,
+    /// [`MLFQ_QUEUE_COUNT`] fixed priority levels, derived from a task's
+    /// own `priority`; picks the highest-priority non-empty level,
+    /// round-robin within it
+    Mlfq,
+}
+
+/// which of [`SchedPolicy`]'s scheduling disciplines
+/// [`crate::task::TaskManager::find_next_task`] actually runs
+///
+/// The request this answers asked for a kernel command-line switch
+/// (`-sched=...`) parsed at boot; this kernel's own entry point,
+/// `crate::main::rust_main`, takes no arguments, and there is no
+/// `entry.asm`/SBI boot-argument plumbing anywhere in this source tree to
+/// hand it one through — so, like [`crate::log::LOG_LEVEL`] and
+/// [`VERBOSE_EXIT_STATS`], this is a build-time constant to flip and
+/// recompile rather than one parsed at runtime. Dispatching through a real
+/// `dyn Scheduler` trait object, per the request's literal wording, isn't
+/// possible either: this crate links no `alloc`, so there's no `Box` to
+/// own one in. Matching on this enum is the same "enum naming a kind"
+/// substitute for a trait object already used throughout `crate::task`
+/// (see e.g. [`crate::task::FileDescriptor`]'s own doc comment).
+///
+/// No tests boot each policy and compare run-time stats, as the request
+/// also asked for: this `#![no_std]`/`#![no_main]` crate has no `[[test]]`
+/// target or host test harness anywhere in this source tree to run one in.
+pub const SCHED_POLICY: SchedPolicy = SchedPolicy::Stride;
+
+/// the number of distinct priority levels [`SchedPolicy::Mlfq`] sorts
+/// `Ready` tasks into, derived from a task's own priority (see
+/// `crate::task::mlfq_level`); the documented `[2, MAX_PRIO]` priority
+/// range is split into this many equal bands
+pub const MLFQ_QUEUE_COUNT: usize = 4;
+
+/// the most times in a row `sys_yield_to` will directly hand a hart off to
+/// the same target task before falling back to an ordinary fair yield for
+/// one cycle; see [`crate::task::TaskManager::yield_to_current`]
+pub const YIELD_TO_FAIRNESS_CAP: usize = 4;
+
+/// whether every source of run-to-run nondeterminism this kernel has any
+/// control over is pinned to a fixed value, so that two boots loading the
+/// same set of apps produce byte-identical `sys_listtasks` output and
+/// identical per-task syscall counts
+///
+/// The request this answers asked for a boot flag parsed at runtime; like
+/// [`SCHED_POLICY`], this kernel's entry point takes no arguments and there
+/// is no boot-argument plumbing anywhere in this source tree to hand one
+/// through, so this is a build-time constant to flip and recompile instead.
+///
+/// [`crate::task::TaskManager::find_next_task`]'s stride tie-break and every
+/// task's initial stride/pass (see `TaskControlBlock::blank`) are already
+/// deterministic with no flag needed — ties resolve to the lowest task-slot
+/// index because ties are found by scanning `0..MAX_APP_NUM` in order, and
+/// every task starts at `stride: 0` regardless of load order or timing. The
+/// same goes for [`crate::task::mlfq_next`]'s round-robin tie-break and
+/// [`crate::task::aging_adjusted_stride`]'s anti-starvation boost, which
+/// only ever depends on elapsed wall time, never on this module's RNG. What
+/// this flag actually pins down is the two places this kernel's own
+/// [`crate::rng`] module feeds into task setup: it forces
+/// [`ASLR_STACK_RANGE`]'s randomized initial stack pointer (see
+/// `crate::loader::aslr_stack_top`) to the deterministic top-of-stack case
+/// regardless of what [`ASLR_STACK_RANGE`] is otherwise set to, and it seeds
+/// `crate::rng`'s generator from a fixed constant instead of
+/// [`crate::timer::get_cycles`] at first use, the same seed
+/// [`crate::rng::GRND_DETERMINISTIC`] already uses for an individual
+/// `sys_getrandom` call.
+///
+/// No test boots the same app set twice under this flag and diffs the
+/// `sys_listtasks`/syscall-count output, as the request also asked for:
+/// this `#![no_std]`/`#![no_main]` crate has no `[[test]]` target or host
+/// test harness anywhere in this source tree to run one in.
+pub const DETERMINISTIC_MODE: bool = false;
+/// whether `sys_pagewalk` (see
+/// [`crate::syscall::process::sys_pagewalk`]) is enabled; off by default
+/// for the same reason as [`IRQ_LATENCY_STATS`] — a debug-only introspection
+/// hook a normal run has no use for, gated behind a build-time flag rather
+/// than a runtime privilege check the way `sys_shutdown` gates its
+/// [`crate::task::INITPROC_PID`]-only actions, since this call only ever
+/// reports on the calling task's own memory, not another task's — there's
+/// no other task's data to protect from it in the first place, only the
+/// bit of always-on overhead a permanently-enabled debug syscall would add
+/// to `SYSCALL_IDS`'s dispatch table for every build.
+pub const PAGEWALK_DEBUG: bool = false;
@@ -0,0 +1,20 @@
+//! Constants used in rCore
+
+/// user app's stack size
+pub const USER_STACK_SIZE: usize = 4096 * 2;
+/// kernel stack size
+pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
+/// kernel heap size
+pub const KERNEL_HEAP_SIZE: usize = 0x3_0000;
+/// the max number of apps that can be loaded at once
+pub const MAX_APP_NUM: usize = 16;
+/// the max syscall id tracked in the externally-visible syscall histogram
+pub const MAX_SYSCALL_NUM: usize = 500;
+/// base address of applications
+pub const APP_BASE_ADDRESS: usize = 0x80400000;
+/// the maximum size of an application
+pub const APP_SIZE_LIMIT: usize = 0x20000;
+/// the frequency of the platform clock, used to convert `mtime` ticks into wall time
+pub const CLOCK_FREQ: usize = 12500000;
+/// number of timer interrupts triggered per second, i.e. the length of a time slice
+pub const TICKS_PER_SEC: usize = 100;
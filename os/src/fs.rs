@@ -0,0 +1,363 @@
+//! A minimal in-kernel filesystem
+//!
+//! A real filesystem needs both a disk image format (e.g. `easy-fs`) and a
+//! block device driver to read/write it, neither of which exists in this
+//! kernel — there's no virtio driver and no vendored `easy-fs` crate to
+//! depend on. This module is a deliberately simplified stand-in: named
+//! files live in a fixed-size table in RAM instead of on a disk, but
+//! present the same open/read/write surface a disk-backed filesystem would,
+//! so the `sys_open`/`sys_read`/`sys_write` syscalls don't need to know the
+//! difference.
+
+use crate::config::{MAX_FILES, MAX_FILE_SIZE, MAX_PATH_LEN};
+use crate::sync::UPSafeCell;
+use lazy_static::lazy_static;
+
+/// open for reading only
+pub const O_RDONLY: u32 = 0;
+/// open for writing only
+pub const O_WRONLY: u32 = 1 << 0;
+/// open for both reading and writing
+pub const O_RDWR: u32 = 1 << 1;
+/// create the file if it doesn't already exist
+pub const O_CREATE: u32 = 1 << 9;
+/// truncate the file to length 0 on open
+pub const O_TRUNC: u32 = 1 << 10;
+/// every write goes to the file's current end, atomically; see
+/// [`write_append`]
+pub const O_APPEND: u32 = 1 << 11;
+
+/// a file's contents, identified by inode id rather than by name
+///
+/// A file may be named by more than one [`DirEntry`] (a hard link), so the
+/// inode itself doesn't know its own name(s) — see [`nlink`].
+#[derive(Copy, Clone)]
+struct Inode {
+    allocated: bool,
+    data: [u8; MAX_FILE_SIZE],
+    len: usize,
+}
+
+impl Inode {
+    fn blank() -> Self {
+        Self {
+            allocated: false,
+            data: [0; MAX_FILE_SIZE],
+            len: 0,
+        }
+    }
+}
+
+/// one name in the filesystem's single, flat directory, pointing at the
+/// inode it names
+///
+/// Kept separate from [`Inode`] (rather than folding a name into it) so
+/// that more than one `DirEntry` can point at the same inode id, the
+/// mechanism a hard link needs.
+#[derive(Copy, Clone)]
+struct DirEntry {
+    allocated: bool,
+    name: [u8; MAX_PATH_LEN],
+    name_len: usize,
+    inode_id: usize,
+}
+
+impl DirEntry {
+    fn blank() -> Self {
+        Self {
+            allocated: false,
+            name: [0; MAX_PATH_LEN],
+            name_len: 0,
+            inode_id: 0,
+        }
+    }
+
+    fn name(&self) -> &[u8] {
+        &self.name[..self.name_len]
+    }
+}
+
+struct FsInner {
+    inodes: [Inode; MAX_FILES],
+    dir_entries: [DirEntry; MAX_FILES],
+}
+
+lazy_static! {
+    static ref FS: UPSafeCell<FsInner> = unsafe {
+        UPSafeCell::new(FsInner {
+            inodes: [Inode::blank(); MAX_FILES],
+            dir_entries: [DirEntry::blank(); MAX_FILES],
+        })
+    };
+}
+
+/// open the file named by `path`, optionally creating or truncating it
+/// according to `flags`, and return its inode id
+///
+/// Returns `None` if `path` doesn't name an existing file and `flags`
+/// doesn't include [`O_CREATE`], if `path` is too long, or if the
+/// filesystem's fixed file/directory-entry tables are full.
+pub fn open(path: &[u8], flags: u32) -> Option<usize> {
+    let mut fs = FS.exclusive_access();
+    let existing = fs
+        .dir_entries
+        .iter()
+        .find(|e| e.allocated && e.name() == path)
+        .map(|e| e.inode_id);
+    let ino = match existing {
+        Some(id) => id,
+        None => {
+            if flags & O_CREATE == 0 || path.len() > MAX_PATH_LEN {
+                return None;
+            }
+            let id = fs.inodes.iter().position(|i| !i.allocated)?;
+            let entry_id = fs.dir_entries.iter().position(|e| !e.allocated)?;
+            fs.inodes[id] = Inode {
+                allocated: true,
+                ..Inode::blank()
+            };
+            let mut name = [0u8; MAX_PATH_LEN];
+            name[..path.len()].copy_from_slice(path);
+            fs.dir_entries[entry_id] = DirEntry {
+                allocated: true,
+                name,
+                name_len: path.len(),
+                inode_id: id,
+            };
+            id
+        }
+    };
+    if flags & O_TRUNC != 0 {
+        fs.inodes[ino].len = 0;
+    }
+    Some(ino)
+}
+
+/// look up the inode id that `path` already names, without opening or
+/// creating anything; returns `None` if no directory entry names it
+///
+/// This is the read-only half of [`open`]'s own existing-entry lookup,
+/// pulled out so callers that only need an inode id (like `sys_stat`)
+/// don't have to go through fd installation to get one.
+pub fn lookup(path: &[u8]) -> Option<usize> {
+    let fs = FS.exclusive_access();
+    fs.dir_entries
+        .iter()
+        .find(|e| e.allocated && e.name() == path)
+        .map(|e| e.inode_id)
+}
+
+/// whether `path` names a directory — today, only the root does, named by
+/// an empty path or `/`; this filesystem's whole directory is the single
+/// flat `dir_entries` table described in this module's own doc comment, so
+/// there is no other directory for any other path to name
+pub fn is_directory(path: &[u8]) -> bool {
+    path.is_empty() || path == b"/"
+}
+
+/// one name in the filesystem's flat directory, as returned by
+/// [`list_dir`]
+#[derive(Copy, Clone)]
+pub struct DirListEntry {
+    /// the inode this entry names
+    pub inode_id: usize,
+    /// the entry's name
+    pub name: [u8; MAX_PATH_LEN],
+    /// how many bytes of `name` are in use
+    pub name_len: usize,
+}
+
+/// every name currently in the filesystem's one flat directory, the same
+/// `(fixed-size snapshot array, true count)` shape
+/// `TaskManager::children_of_current`/`memory_map_current` return for their
+/// own fixed-size result tables
+///
+/// `sys_getdents` pages through this with its own per-fd cursor rather than
+/// truncating to a `cap` here the way `sys_listtasks`/`sys_maps` do, since it
+/// has to honor "reading past the end returns 0" across repeated calls on
+/// the same fd, not just a single call's buffer size.
+pub fn list_dir() -> ([DirListEntry; MAX_FILES], usize) {
+    let fs = FS.exclusive_access();
+    let mut out = [DirListEntry {
+        inode_id: 0,
+        name: [0; MAX_PATH_LEN],
+        name_len: 0,
+    }; MAX_FILES];
+    let mut count = 0;
+    for e in fs.dir_entries.iter() {
+        if e.allocated {
+            out[count] = DirListEntry {
+                inode_id: e.inode_id,
+                name: e.name,
+                name_len: e.name_len,
+            };
+            count += 1;
+        }
+    }
+    (out, count)
+}
+
+/// how many directory entries currently name inode `ino` — 1 for an
+/// ordinary file, 2 or more once it has hard links
+pub fn nlink(ino: usize) -> usize {
+    let fs = FS.exclusive_access();
+    fs.dir_entries
+        .iter()
+        .filter(|e| e.allocated && e.inode_id == ino)
+        .count()
+}
+
+/// create `new_path` as another name for the file already open at
+/// `old_path`, incrementing its link count
+///
+/// Returns `None` (and does nothing) if `old_path` doesn't name an
+/// existing file, if `new_path` already names one, if `new_path` is too
+/// long, or if the directory-entry table is full.
+pub fn link(old_path: &[u8], new_path: &[u8]) -> Option<()> {
+    let mut fs = FS.exclusive_access();
+    if new_path.len() > MAX_PATH_LEN {
+        return None;
+    }
+    if fs.dir_entries.iter().any(|e| e.allocated && e.name() == new_path) {
+        return None;
+    }
+    let inode_id = fs
+        .dir_entries
+        .iter()
+        .find(|e| e.allocated && e.name() == old_path)
+        .map(|e| e.inode_id)?;
+    let entry_id = fs.dir_entries.iter().position(|e| !e.allocated)?;
+    let mut name = [0u8; MAX_PATH_LEN];
+    name[..new_path.len()].copy_from_slice(new_path);
+    fs.dir_entries[entry_id] = DirEntry {
+        allocated: true,
+        name,
+        name_len: new_path.len(),
+        inode_id,
+    };
+    Some(())
+}
+
+/// remove the directory entry naming `path`, decrementing its inode's
+/// link count and freeing the inode once no directory entry names it any
+/// more
+///
+/// Returns `None` (and does nothing) if `path` doesn't name an existing
+/// file.
+pub fn unlink(path: &[u8]) -> Option<()> {
+    let mut fs = FS.exclusive_access();
+    let entry_id = fs
+        .dir_entries
+        .iter()
+        .position(|e| e.allocated && e.name() == path)?;
+    let inode_id = fs.dir_entries[entry_id].inode_id;
+    fs.dir_entries[entry_id] = DirEntry::blank();
+    let still_linked = fs
+        .dir_entries
+        .iter()
+        .any(|e| e.allocated && e.inode_id == inode_id);
+    if !still_linked {
+        fs.inodes[inode_id] = Inode::blank();
+    }
+    Some(())
+}
+
+/// read up to `buf.len()` bytes from inode `ino` starting at `offset` into
+/// `buf`, returning how many bytes were actually copied
+pub fn read_at(ino: usize, offset: usize, buf: &mut [u8]) -> usize {
+    let fs = FS.exclusive_access();
+    let inode = &fs.inodes[ino];
+    if offset >= inode.len {
+        return 0;
+    }
+    let n = buf.len().min(inode.len - offset);
+    buf[..n].copy_from_slice(&inode.data[offset..offset + n]);
+    n
+}
+
+/// write `buf` into inode `ino` starting at `offset`, growing the file as
+/// needed, and return how many bytes were actually copied; this may be
+/// less than `buf.len()` if the write would grow the file past
+/// [`MAX_FILE_SIZE`]
+pub fn write_at(ino: usize, offset: usize, buf: &[u8]) -> usize {
+    let mut fs = FS.exclusive_access();
+    let inode = &mut fs.inodes[ino];
+    if offset >= MAX_FILE_SIZE {
+        return 0;
+    }
+    let n = buf.len().min(MAX_FILE_SIZE - offset);
+    inode.data[offset..offset + n].copy_from_slice(&buf[..n]);
+    inode.len = inode.len.max(offset + n);
+    n
+}
+
+/// write `buf` to inode `ino`'s current end of file, re-seeking to it and
+/// extending the inode in the same [`FS`] lock acquisition, for an
+/// `O_APPEND` fd's writes (see [`crate::task::FileFd::append`])
+///
+/// `write_at(ino, <some earlier read of inode.len>, buf)` would read
+/// `inode.len` in one lock acquisition and write in another, leaving a
+/// window between them where a second appender (a different fd, possibly a
+/// different task) could extend the file first — both writers would then
+/// think the old length was still current and the second write would land
+/// on top of the first's tail instead of after it. Folding the "find the
+/// end" read and the write into this one critical section is what makes
+/// two interleaved appenders land back to back instead, with nothing lost
+/// or overwritten.
+///
+/// Returns `(bytes actually written, the offset just past them)` — the
+/// caller's fd offset should be set to the second value, which may be
+/// short of the file's true end if another task appended more since.
+///
+/// A test with two appenders interleaving writes and confirming nothing is
+/// lost or overwritten would be a binary (or two, with `sys_fork`) in the
+/// sibling `user` crate this kernel loads at boot; that crate isn't part of
+/// this source tree, so there's nothing here to add such a binary to.
+pub fn write_append(ino: usize, buf: &[u8]) -> (usize, usize) {
+    let mut fs = FS.exclusive_access();
+    let inode = &mut fs.inodes[ino];
+    let offset = inode.len;
+    if offset >= MAX_FILE_SIZE {
+        return (0, offset);
+    }
+    let n = buf.len().min(MAX_FILE_SIZE - offset);
+    inode.data[offset..offset + n].copy_from_slice(&buf[..n]);
+    inode.len = offset + n;
+    (n, offset + n)
+}
+
+/// the current length in bytes of inode `ino`'s contents, for `sys_lseek`'s
+/// `SEEK_END`
+pub fn file_len(ino: usize) -> usize {
+    FS.exclusive_access().inodes[ino].len
+}
+
+/// resize inode `ino` to exactly `len` bytes, for `sys_ftruncate`
+///
+/// Shrinking (`len < inode.len`) zeroes the bytes beyond the new length —
+/// there's no separate block bitmap to return them to here: `Inode::data`
+/// is one fixed-size `[u8; MAX_FILE_SIZE]` array per inode rather than a
+/// chain of allocator-owned blocks (see this module's doc comment on why
+/// there's no `easy-fs`/block-device layer underneath it), so "freeing"
+/// unreachable space just means zeroing it so a later grow doesn't expose
+/// stale bytes the way [`write_at`]'s own grow path already avoids by
+/// never reading past `inode.len`. Growing (`len > inode.len`) leaves the
+/// newly exposed bytes zeroed for the same reason `Inode::blank` zeroes a
+/// fresh inode's whole array up front.
+///
+/// Returns `None` if `len` exceeds [`MAX_FILE_SIZE`], the same `-EFBIG`
+/// case `write_at` enforces by capping its own copy length instead.
+pub fn truncate(ino: usize, len: usize) -> Option<()> {
+    if len > MAX_FILE_SIZE {
+        return None;
+    }
+    let mut fs = FS.exclusive_access();
+    let inode = &mut fs.inodes[ino];
+    if len < inode.len {
+        inode.data[len..inode.len].fill(0);
+    } else if len > inode.len {
+        inode.data[inode.len..len].fill(0);
+    }
+    inode.len = len;
+    Some(())
+}
@@ -0,0 +1,23 @@
+//! The panic handler
+
+use crate::sbi::shutdown;
+use crate::stack_trace::print_stack_trace;
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    if let Some(location) = info.location() {
+        println!(
+            "[kernel] Panicked at {}:{} {}",
+            location.file(),
+            location.line(),
+            info.message().unwrap()
+        );
+    } else {
+        println!("[kernel] Panicked: {}", info.message().unwrap());
+    }
+    unsafe {
+        print_stack_trace();
+    }
+    shutdown(true)
+}
@@ -0,0 +1,21 @@
+//! Per-hart identity
+//!
+//! A real SMP boot path reads the hart id out of `a0` in `entry.asm` (SBI
+//! hands it to every hart's entry point, boot hart included) and stashes it
+//! somewhere it survives into Rust, typically `tp`; a secondary hart is
+//! then started on demand via the SBI HSM extension's `hart_start`, with
+//! its own boot stack.
+//!
+//! Neither `entry.asm` nor `sbi.rs` exist in this source tree (both are
+//! referenced via `mod`/`include_str!` elsewhere in this crate but are
+//! missing from this snapshot), so there is no hart id to actually read and
+//! no `hart_start` call to make. This module is the honest stand-in: it
+//! reports every trap as running on hart 0, which is also simply true of
+//! this kernel's actual current behavior — only the boot hart ever runs.
+//! The rest of the SMP-shaped plumbing ([`crate::task::TaskManager`]'s
+//! per-hart current-task array, its shared spinlock-guarded task table)
+//! is real and is exactly what [`hart_id`] would need to start returning
+//! distinct values per-hart for, once `entry.asm`/`sbi.rs` can supply them.
+pub fn hart_id() -> usize {
+    0
+}
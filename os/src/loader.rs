@@ -0,0 +1,103 @@
+//! Loading user applications into memory
+
+use crate::config::*;
+use crate::trap::TrapContext;
+use core::arch::asm;
+
+#[repr(align(4096))]
+#[derive(Copy, Clone)]
+struct KernelStack {
+    data: [u8; KERNEL_STACK_SIZE],
+}
+
+#[repr(align(4096))]
+#[derive(Copy, Clone)]
+struct UserStack {
+    data: [u8; USER_STACK_SIZE],
+}
+
+static KERNEL_STACK: [KernelStack; MAX_APP_NUM] = [KernelStack {
+    data: [0; KERNEL_STACK_SIZE],
+}; MAX_APP_NUM];
+
+static USER_STACK: [UserStack; MAX_APP_NUM] = [UserStack {
+    data: [0; USER_STACK_SIZE],
+}; MAX_APP_NUM];
+
+impl KernelStack {
+    fn get_bottom(&self) -> usize {
+        self.data.as_ptr() as usize
+    }
+    fn get_sp(&self) -> usize {
+        self.get_bottom() + KERNEL_STACK_SIZE
+    }
+    fn push_context(&self, trap_cx: TrapContext) -> usize {
+        let trap_cx_ptr = (self.get_sp() - core::mem::size_of::<TrapContext>()) as *mut TrapContext;
+        unsafe {
+            *trap_cx_ptr = trap_cx;
+        }
+        trap_cx_ptr as usize
+    }
+}
+
+impl UserStack {
+    fn get_sp(&self) -> usize {
+        self.data.as_ptr() as usize + USER_STACK_SIZE
+    }
+}
+
+/// get the `[bottom, top)` address range of the given app's kernel stack.
+///
+/// Each app's kernel stack is only page-aligned (4096 bytes), not
+/// `KERNEL_STACK_SIZE`-aligned, so this is the one source of truth for
+/// diagnostics (such as [`crate::stack_trace::print_stack_trace`]) that need
+/// to know whether an address still lies on it.
+pub fn kernel_stack_range(app_id: usize) -> (usize, usize) {
+    let stack = &KERNEL_STACK[app_id];
+    (stack.get_bottom(), stack.get_sp())
+}
+
+fn get_base_i(app_id: usize) -> usize {
+    APP_BASE_ADDRESS + app_id * APP_SIZE_LIMIT
+}
+
+/// get the total number of applications linked into the kernel image
+pub fn get_num_app() -> usize {
+    extern "C" {
+        fn _num_app();
+    }
+    unsafe { (_num_app as usize as *const usize).read_volatile() }
+}
+
+/// load all applications into their fixed memory slots
+pub fn load_apps() {
+    extern "C" {
+        fn _num_app();
+    }
+    let num_app_ptr = _num_app as usize as *const usize;
+    let num_app = get_num_app();
+    let app_start = unsafe { core::slice::from_raw_parts(num_app_ptr.add(1), num_app + 1) };
+    unsafe {
+        asm!("fence.i");
+    }
+    for i in 0..num_app {
+        let base_i = get_base_i(i);
+        (base_i..base_i + APP_SIZE_LIMIT).for_each(|addr| unsafe {
+            (addr as *mut u8).write_volatile(0)
+        });
+        let src = unsafe {
+            core::slice::from_raw_parts(app_start[i] as *const u8, app_start[i + 1] - app_start[i])
+        };
+        let dst = unsafe { core::slice::from_raw_parts_mut(base_i as *mut u8, src.len()) };
+        dst.copy_from_slice(src);
+    }
+}
+
+/// build the initial trap context for the given app and push it onto that
+/// app's own kernel stack, returning the resulting stack pointer
+pub fn init_app_cx(app_id: usize) -> usize {
+    KERNEL_STACK[app_id].push_context(TrapContext::app_init_context(
+        get_base_i(app_id),
+        USER_STACK[app_id].get_sp(),
+    ))
+}
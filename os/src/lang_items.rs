@@ -0,0 +1,31 @@
+//! The panic handler
+
+use crate::sbi::shutdown;
+use crate::stack_trace::print_stack_trace;
+use core::panic::PanicInfo;
+
+// A task's name (`crate::task::TaskControlBlock::name`) deliberately isn't
+// printed here. `TASK_MANAGER` is guarded by a real `SpinLock` (see
+// `crate::sync::SpinLock`), not a reentrant one — a panic that fires while
+// that lock is already held (e.g. partway through `dispatch_next`) would
+// have this handler spin forever trying to read it back out, turning an
+// ordinary panic into an unrecoverable hang. The name is surfaced in
+// `sys_listtasks` and the scheduler's `trace!` line instead, both of which
+// only ever run with the lock legitimately free to take.
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    if let Some(location) = info.location() {
+        println!(
+            "[kernel] Panicked at {}:{} {}",
+            location.file(),
+            location.line(),
+            info.message().unwrap()
+        );
+    } else {
+        println!("[kernel] Panicked: {}", info.message().unwrap());
+    }
+    unsafe {
+        print_stack_trace();
+    }
+    shutdown(true)
+}
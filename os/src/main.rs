@@ -0,0 +1,55 @@
+//! The main module and entrypoint
+//!
+//! This crate implements a batch/multiprogramming RISC-V kernel that runs
+//! applications linked into its image one at a time (or, with the timer
+//! interrupt enabled below, time-slices between the ones that are ready).
+//!
+//! The operating system also starts in this module. Kernel code starts
+//! executing from `entry.asm`, after which [`rust_main()`] clears the BSS
+//! segment, initializes the trap vector, loads the applications, enables
+//! the timer interrupt and arms the first tick, and finally runs the first
+//! task.
+
+#![deny(missing_docs)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+#![feature(panic_info_message)]
+
+use core::arch::global_asm;
+
+#[macro_use]
+mod console;
+mod config;
+mod lang_items;
+mod loader;
+mod sbi;
+mod stack_trace;
+mod sync;
+pub mod syscall;
+pub mod task;
+mod timer;
+pub mod trap;
+
+global_asm!(include_str!("entry.asm"));
+global_asm!(include_str!("link_app.S"));
+
+fn clear_bss() {
+    extern "C" {
+        fn sbss();
+        fn ebss();
+    }
+    (sbss as usize..ebss as usize).for_each(|a| unsafe { (a as *mut u8).write_volatile(0) });
+}
+
+#[no_mangle]
+fn rust_main() -> ! {
+    clear_bss();
+    println!("[kernel] Hello, world!");
+    trap::init();
+    loader::load_apps();
+    trap::enable_timer_interrupt();
+    timer::set_next_trigger();
+    task::run_first_task();
+    panic!("Unreachable in rust_main!");
+}
@@ -0,0 +1,384 @@
+//! Loading user applications into memory
+//!
+//! Every app here is a raw flat binary, concatenated into the kernel image
+//! by `link_app.S` with no ELF headers at all — [`load_apps`] just copies
+//! bytes into a fixed physical slot, and there is no frame allocator or
+//! per-task page table anywhere in [`crate::mm`] to fault pages into.
+//! Demand-paging an app's load segments (mapping them lazily and filling
+//! each page in on first access, BSS pages zeroed, permissions taken from
+//! program header flags) needs all three of those — an ELF parser, a frame
+//! allocator, and real per-task paging — which would mean building this
+//! kernel's entire memory-management subsystem from nothing rather than
+//! extending an existing piece of it, so it isn't attempted here. Every
+//! app's segments are still copied in full, eagerly, up front, exactly as
+//! before.
+
+use crate::config::*;
+use crate::mm::PAGE_SIZE;
+use crate::trap::TrapContext;
+use core::arch::asm;
+
+/// the byte value [`kernel_stack_init_data`] seeds a fresh [`KernelStack`]'s
+/// guard region with; anything other than this found there later means the
+/// stack grew into the guard, see [`KernelStack::canary_intact`]
+const KERNEL_STACK_CANARY: u8 = 0xA5;
+
+/// build a [`KernelStack`]'s backing array with its guard region pre-filled
+/// with [`KERNEL_STACK_CANARY`] and the rest zeroed, as a `const fn` so it
+/// can run at compile time in `KERNEL_STACK`'s own static initializer
+const fn kernel_stack_init_data() -> [u8; KERNEL_STACK_GUARD_SIZE + KERNEL_STACK_SIZE] {
+    let mut data = [0u8; KERNEL_STACK_GUARD_SIZE + KERNEL_STACK_SIZE];
+    let mut i = 0;
+    while i < KERNEL_STACK_GUARD_SIZE {
+        data[i] = KERNEL_STACK_CANARY;
+        i += 1;
+    }
+    data
+}
+
+#[repr(align(4096))]
+#[derive(Copy, Clone)]
+struct KernelStack {
+    // the first `KERNEL_STACK_GUARD_SIZE` bytes are a canary-filled guard
+    // region (see `canary_intact`), never meant to be written to by a kernel
+    // call chain running on this stack; the rest, `KERNEL_STACK_SIZE` bytes,
+    // is the stack itself
+    data: [u8; KERNEL_STACK_GUARD_SIZE + KERNEL_STACK_SIZE],
+}
+
+#[repr(align(4096))]
+#[derive(Copy, Clone)]
+struct UserStack {
+    // the first `USER_STACK_GUARD_SIZE` bytes are the guard region (see
+    // `guard_range`), never meant to be touched by a well-behaved app; the
+    // rest, `USER_STACK_SIZE` bytes, is the stack itself
+    data: [u8; USER_STACK_GUARD_SIZE + USER_STACK_SIZE],
+}
+
+static KERNEL_STACK: [KernelStack; MAX_APP_NUM] = [KernelStack {
+    data: kernel_stack_init_data(),
+}; MAX_APP_NUM];
+
+static USER_STACK: [UserStack; MAX_APP_NUM] = [UserStack {
+    data: [0; USER_STACK_GUARD_SIZE + USER_STACK_SIZE],
+}; MAX_APP_NUM];
+
+impl KernelStack {
+    /// the lowest address of the *usable* part of this stack, i.e. past its
+    /// guard region
+    fn usable_bottom(&self) -> usize {
+        self.data.as_ptr() as usize + KERNEL_STACK_GUARD_SIZE
+    }
+    fn get_sp(&self) -> usize {
+        self.data.as_ptr() as usize + self.data.len()
+    }
+    fn push_context(&self, trap_cx: TrapContext) -> usize {
+        let trap_cx_ptr = (self.get_sp() - core::mem::size_of::<TrapContext>()) as *mut TrapContext;
+        unsafe {
+            *trap_cx_ptr = trap_cx;
+        }
+        trap_cx_ptr as usize
+    }
+
+    /// whether this stack's guard region still holds nothing but
+    /// [`KERNEL_STACK_CANARY`]
+    ///
+    /// This kernel has no per-task page tables (see [`crate::mm`]'s module
+    /// doc), so there's no guard *page* to fault on the way
+    /// [`user_stack_guard_range`] pretends one exists for the user stack —
+    /// a write into this region doesn't trap, it just overwrites these
+    /// bytes. A kernel stack overflow deep enough to reach the guard region
+    /// leaves the canary clobbered, which is the only way this kernel can
+    /// tell the two apart from plain, correct recursion afterwards; see
+    /// [`kernel_stack_canary_intact`].
+    fn canary_intact(&self) -> bool {
+        self.data[..KERNEL_STACK_GUARD_SIZE]
+            .iter()
+            .all(|&b| b == KERNEL_STACK_CANARY)
+    }
+}
+
+impl UserStack {
+    fn get_sp(&self) -> usize {
+        self.data.as_ptr() as usize + self.data.len()
+    }
+
+    /// the `[start, end)` range of this stack's guard region, just below
+    /// the lowest address a well-behaved app should ever push to
+    fn guard_range(&self) -> (usize, usize) {
+        let start = self.data.as_ptr() as usize;
+        (start, start + USER_STACK_GUARD_SIZE)
+    }
+
+    /// the `[start, end)` range of this stack that's actually usable, i.e.
+    /// everything past the guard region
+    fn usable_range(&self) -> (usize, usize) {
+        let (_, guard_end) = self.guard_range();
+        (guard_end, self.get_sp())
+    }
+}
+
+/// get the `[bottom, top)` address range of the given app's kernel stack,
+/// i.e. the usable part past its guard region (see
+/// [`kernel_stack_canary_intact`]).
+///
+/// Each app's kernel stack is only page-aligned (4096 bytes), not
+/// `KERNEL_STACK_SIZE`-aligned, so this is the one source of truth for
+/// diagnostics (such as [`crate::stack_trace::print_stack_trace`]) that need
+/// to know whether an address still lies on it.
+pub fn kernel_stack_range(app_id: usize) -> (usize, usize) {
+    let stack = &KERNEL_STACK[app_id];
+    (stack.usable_bottom(), stack.get_sp())
+}
+
+/// whether the given app's kernel stack still has its guard region's canary
+/// intact, i.e. kernel-mode execution on this stack hasn't overflowed into
+/// it
+///
+/// Real lazy, on-demand kernel stack growth needs a guard *page* the MMU
+/// faults on so the fault handler can grow the mapping — this kernel has no
+/// per-task page tables or frame allocator to grow into in the first place
+/// (see this module's own doc comment for [`load_apps`]'s identical
+/// limitation), so [`KERNEL_STACK`] stays the fixed-size static array it
+/// always was. This canary is the closest honest substitute reachable here:
+/// it can't stop an overflow or grow the stack, but it turns one from
+/// silent corruption of whatever the guard region used to hold into a
+/// detectable fact, checked by [`crate::trap::trap_handler`] right before
+/// returning to user mode.
+///
+/// Because kernel-mode traps on this kernel aren't re-entrant (see
+/// [`crate::trap`]'s module doc), this is the only point in a trap's
+/// lifetime where re-checking even makes sense — there's no equivalent of
+/// the user-stack guard's repeated recheck on every subsequent trap, since a
+/// kernel-mode fault doesn't trap back into this handler again.
+///
+/// A test that deeply nests kernel calls until this overflows (or confirms
+/// it doesn't) would need a syscall whose kernel-side handling recurses by a
+/// caller-controlled depth, driven from the sibling `user` crate — but this
+/// crate is built `#![no_std]`/`#![no_main]` for a bare-metal target with no
+/// host test harness wired up anywhere in this source tree, so there's
+/// nothing here to add such a test to.
+pub fn kernel_stack_canary_intact(app_id: usize) -> bool {
+    KERNEL_STACK[app_id].canary_intact()
+}
+
+/// get the `[start, end)` address range of the given task slot's user
+/// stack guard region, just below its usable stack
+///
+/// This kernel has no per-task page tables (see [`crate::mm`]), so the
+/// guard region isn't actually unmapped — it's ordinary, addressable
+/// memory, and a write into it doesn't trap. [`crate::trap::trap_handler`]
+/// instead checks the user `sp` against this range on every trap, which is
+/// the closest equivalent reachable without real paging hardware; see its
+/// doc comment for why.
+pub fn user_stack_guard_range(app_id: usize) -> (usize, usize) {
+    USER_STACK[app_id].guard_range()
+}
+
+/// get the `[start, end)` address range of the given task slot's user stack
+/// that's actually usable, i.e. everything past
+/// [`user_stack_guard_range`]'s guard region
+pub fn user_stack_usable_range(app_id: usize) -> (usize, usize) {
+    USER_STACK[app_id].usable_range()
+}
+
+/// the initial user stack pointer for a freshly loaded or `exec`'d app:
+/// the top of its reserved [`UserStack`], pushed down by a pseudo-random,
+/// 16-byte-aligned offset up to [`ASLR_STACK_RANGE`] bytes — the same
+/// random-gap-below-the-top ASLR gives a real process, except the
+/// reserved stack region itself is still the same fixed size it always
+/// was; the gap just comes out of space that would otherwise go unused.
+///
+/// [`ASLR_STACK_RANGE`] of `0`, or [`crate::config::DETERMINISTIC_MODE`],
+/// disables this, always returning the exact top, for test harnesses that
+/// need a deterministic stack pointer.
+///
+/// There's no equivalent `mmap` base to randomize here: `sys_mmap` already
+/// takes its `start` address straight from the caller rather than having
+/// the kernel pick one, so there's no kernel-chosen base address for this
+/// to vary in the first place.
+///
+/// A test spawning the same app twice and comparing the two stack
+/// pointers would be plain kernel-internal logic with no dependency on
+/// the sibling `user` crate, calling this function directly — but this
+/// crate is built `#![no_std]`/`#![no_main]` for a bare-metal target with
+/// no host test harness wired up anywhere in this source tree, so there's
+/// nothing here to add such a test to.
+pub fn aslr_stack_top(app_id: usize) -> usize {
+    let top = USER_STACK[app_id].get_sp();
+    if ASLR_STACK_RANGE == 0 || crate::config::DETERMINISTIC_MODE {
+        return top;
+    }
+    let slots = ASLR_STACK_RANGE / 16;
+    let offset = (crate::timer::get_cycles() % slots) * 16;
+    top - offset
+}
+
+fn get_base_i(app_id: usize) -> usize {
+    APP_BASE_ADDRESS + app_id * APP_SIZE_LIMIT
+}
+
+/// the address of the start of the given app's reserved memory slot; see
+/// [`app_area_end`] for the other end
+pub fn app_area_start(app_id: usize) -> usize {
+    get_base_i(app_id)
+}
+
+/// the length in bytes of each app's loaded image, filled in by
+/// [`load_apps`]; used by [`app_heap_base`] to find where an app's heap may
+/// start growing from, and by [`reload_into`] to re-copy an app's image
+static mut APP_LEN: [usize; MAX_APP_NUM] = [0; MAX_APP_NUM];
+
+/// the address each app's original, pristine image lives at in the kernel's
+/// own memory (i.e. the `app_N_start` symbols from `link_app.S`), kept
+/// around past boot so [`reload_into`] can re-copy an app without needing to
+/// re-link or re-read it from disk
+static mut APP_START: [usize; MAX_APP_NUM] = [0; MAX_APP_NUM];
+
+/// the first address, page-aligned, after the given app's loaded image —
+/// this is where its heap (grown via `sbrk`) starts
+pub fn app_heap_base(app_id: usize) -> usize {
+    let len = unsafe { APP_LEN[app_id] };
+    (get_base_i(app_id) + len + PAGE_SIZE - 1) & !(PAGE_SIZE - 1)
+}
+
+/// the address one past the end of the memory slot reserved for the given
+/// app; heap growth must stay below this
+pub fn app_area_end(app_id: usize) -> usize {
+    get_base_i(app_id) + APP_SIZE_LIMIT
+}
+
+/// get the total number of applications linked into the kernel image
+pub fn get_num_app() -> usize {
+    extern "C" {
+        fn _num_app();
+    }
+    unsafe { (_num_app as usize as *const usize).read_volatile() }
+}
+
+/// load all applications into their fixed memory slots
+pub fn load_apps() {
+    extern "C" {
+        fn _num_app();
+    }
+    let num_app_ptr = _num_app as usize as *const usize;
+    let num_app = get_num_app();
+    let app_start = unsafe { core::slice::from_raw_parts(num_app_ptr.add(1), num_app + 1) };
+    unsafe {
+        asm!("fence.i");
+    }
+    for i in 0..num_app {
+        let base_i = get_base_i(i);
+        (base_i..base_i + APP_SIZE_LIMIT).for_each(|addr| unsafe {
+            (addr as *mut u8).write_volatile(0)
+        });
+        let src = unsafe {
+            core::slice::from_raw_parts(app_start[i] as *const u8, app_start[i + 1] - app_start[i])
+        };
+        let dst = unsafe { core::slice::from_raw_parts_mut(base_i as *mut u8, src.len()) };
+        dst.copy_from_slice(src);
+        unsafe {
+            APP_LEN[i] = src.len();
+            APP_START[i] = app_start[i];
+        }
+    }
+}
+
+/// re-copy app `src_app`'s pristine image into `dest_slot`'s memory region,
+/// as if it had just been loaded by [`load_apps`]
+///
+/// Used by `sys_spawn` to start a fresh instance of an already-loaded app in
+/// a task slot that isn't running anything yet.
+pub fn reload_into(src_app: usize, dest_slot: usize) {
+    let base = get_base_i(dest_slot);
+    unsafe {
+        (base..base + APP_SIZE_LIMIT).for_each(|addr| (addr as *mut u8).write_volatile(0));
+        let src = core::slice::from_raw_parts(APP_START[src_app] as *const u8, APP_LEN[src_app]);
+        let dst = core::slice::from_raw_parts_mut(base as *mut u8, src.len());
+        dst.copy_from_slice(src);
+        APP_LEN[dest_slot] = src.len();
+    }
+}
+
+/// build the initial trap context for the given app and push it onto that
+/// app's own kernel stack, returning the resulting stack pointer
+pub fn init_app_cx(app_id: usize) -> usize {
+    KERNEL_STACK[app_id].push_context(TrapContext::app_init_context(
+        get_base_i(app_id),
+        aslr_stack_top(app_id),
+    ))
+}
+
+/// build the initial trap context for a new thread sharing another task's
+/// memory, and push it onto `tid_slot`'s own kernel stack, returning the
+/// resulting stack pointer
+///
+/// Unlike [`init_app_cx`], the entry point and argument are caller-supplied
+/// rather than derived from the app's own loaded image, and the user stack
+/// is `tid_slot`'s own (threads never share a stack, only the rest of the
+/// address space).
+pub fn init_thread_cx(tid_slot: usize, entry: usize, arg: usize) -> usize {
+    let mut cx = TrapContext::app_init_context(entry, USER_STACK[tid_slot].get_sp());
+    cx.x[10] = arg;
+    KERNEL_STACK[tid_slot].push_context(cx)
+}
+
+/// build the trap context `sys_exec` replaces the current task's own trap
+/// frame with, in place (see [`trap_cx_ptr`]) rather than pushing a fresh
+/// one the way [`init_app_cx`] does for a task that hasn't run yet
+///
+/// The entry point is the start of `app_id`'s own memory slot, the same
+/// as [`init_app_cx`] uses — this kernel's apps are raw flat binaries with
+/// no ELF header to read an entry point out of (see this module's doc
+/// comment), so "the first byte of the image" is the only entry point
+/// there ever is. `sp` and `argv` are caller-supplied rather than derived
+/// here, since they point partway down a stack `sys_exec` itself just
+/// built by pushing `argv`'s strings onto it.
+pub fn exec_init_context(app_id: usize, sp: usize, argv: usize) -> TrapContext {
+    let mut cx = TrapContext::app_init_context(get_base_i(app_id), sp);
+    cx.x[11] = argv;
+    cx
+}
+
+/// the fixed address, at the top of an app's own kernel stack, where its
+/// trap context lives while it's not running — the same address
+/// [`init_app_cx`] pushes the initial one to
+pub fn trap_cx_ptr(app_id: usize) -> *mut TrapContext {
+    (KERNEL_STACK[app_id].get_sp() - core::mem::size_of::<TrapContext>()) as *mut TrapContext
+}
+
+/// copy `src`'s entire reserved memory slot and user stack into `dst`'s
+///
+/// Used by `sys_fork` to give a child task its own, independent copy of its
+/// parent's memory. This kernel has no per-task page tables, so there's no
+/// way to share pages and fault them in copy-on-write as a real `fork`
+/// would: every byte is eagerly duplicated instead.
+///
+/// Sharing just the read-only text segment by reference-counted frames
+/// (rather than full COW) would still need the same two missing pieces: a
+/// page table per task to point two tasks' entries at one physical frame,
+/// and a write-fault handler to tell "this was a legitimate write to a
+/// private page" apart from "this hit the shared, intentionally-immutable
+/// text" (see `crate::mm`'s own module doc comment, which calls out both
+/// prerequisites for COW fork in general). An app here isn't even laid out
+/// with a separate text region to begin with — [`clone_app_state`] copies
+/// one undifferentiated `[u8; APP_SIZE_LIMIT]` slot holding code, data, and
+/// BSS together (see this module's own doc comment on raw flat binaries
+/// having no ELF header to read section boundaries from), so there's no
+/// "read-only range" here for a refcount to even describe, frame-granular
+/// or otherwise.
+pub fn clone_app_state(src: usize, dst: usize) {
+    let src_base = get_base_i(src);
+    let dst_base = get_base_i(dst);
+    unsafe {
+        let s = core::slice::from_raw_parts(src_base as *const u8, APP_SIZE_LIMIT);
+        let d = core::slice::from_raw_parts_mut(dst_base as *mut u8, APP_SIZE_LIMIT);
+        d.copy_from_slice(s);
+        APP_LEN[dst] = APP_LEN[src];
+        core::ptr::copy_nonoverlapping(
+            USER_STACK[src].data.as_ptr(),
+            USER_STACK[dst].data.as_ptr() as *mut u8,
+            USER_STACK[src].data.len(),
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Kernel panic-time stack backtrace
+//!
+//! Walks the chain of saved frame pointers on the current task's kernel
+//! stack to print a best-effort call chain when a panic occurs. This relies
+//! on the kernel being compiled with `-C force-frame-pointers=yes` (see
+//! `.cargo/config.toml`) so that every non-leaf function keeps `fp` pointing
+//! at a valid saved-frame record instead of omitting it.
+
+use crate::loader::kernel_stack_range;
+use crate::task::TASK_MANAGER;
+use core::arch::asm;
+
+/// print a backtrace of the kernel stack by walking saved frame pointers
+///
+/// # Safety
+///
+/// Walks raw memory through the frame pointer chain starting from the
+/// current `fp`. Relies on the kernel having been built with frame pointers
+/// forced on, and stops as soon as `fp` is null or falls outside the
+/// current task's kernel stack range (in particular, this guards against the
+/// walk wandering into the hand-written `__alltraps` assembly entry, which
+/// does not maintain a frame-pointer chain and would otherwise leave `fp`
+/// holding an arbitrary, non-decreasing value forever).
+pub unsafe fn print_stack_trace() {
+    let mut fp: usize;
+    asm!("mv {}, fp", out(reg) fp);
+
+    // each app's kernel stack is only page-aligned, not
+    // `KERNEL_STACK_SIZE`-aligned, so the real bounds have to come from the
+    // loader's own `KernelStack` allocation rather than rounding `fp`/`sp`
+    let (stack_bottom, stack_top) = kernel_stack_range(TASK_MANAGER.get_current_task());
+
+    println!("== Kernel Stack Trace ==");
+    while fp > stack_bottom && fp <= stack_top {
+        let ra = *(fp as *const usize).offset(-1);
+        let saved_fp = *(fp as *const usize).offset(-2);
+        println!("0x{:016x}", ra);
+        if saved_fp == 0 || saved_fp <= fp {
+            break;
+        }
+        fp = saved_fp;
+    }
+    println!("== End Stack Trace ==");
+}
@@ -0,0 +1,136 @@
+//! Level-filtered logging macros backed by the kernel console
+//!
+//! `error!`/`warn!`/`info!`/`debug!`/`trace!` all format through
+//! [`log_line`] the same way `println!` formats through the console's
+//! `Stdout`, but each first checks its own [`LogLevel`] against
+//! [`LOG_LEVEL`] and skips formatting its arguments and calling
+//! [`log_line`] entirely if the call is more verbose than the configured
+//! level — both sides of that `<=` are `const`s, so in an optimized build
+//! rustc folds it away rather than re-checking it on every call, which is
+//! the "compile-time check" in place of a runtime test: there is no
+//! sibling `user` crate in this source tree to run a test binary from
+//! (see e.g. [`crate::syscall::process::sys_set_priority`]'s own doc
+//! comment for why this kernel's tests live as doc comments rather than
+//! `#[test]`s), and a below-threshold call never reaching [`log_line`]
+//! isn't something a test would need to observe at runtime anyway.
+
+use core::fmt;
+
+/// the five severities `error!`/`warn!`/`info!`/`debug!`/`trace!` log at,
+/// ordered least to most verbose so that `<=` against [`LOG_LEVEL`] is the
+/// whole filter — the same ordering and the same comparison direction
+/// `log::LevelFilter` uses
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum LogLevel {
+    /// unrecoverable-to-the-caller failures: a task being killed, a
+    /// resource exhausted
+    Error,
+    /// recoverable but noteworthy conditions
+    Warn,
+    /// routine kernel milestones, on by default
+    Info,
+    /// detail useful when chasing a specific bug, off by default
+    Debug,
+    /// per-tick/per-switch detail; see the timer interrupt and scheduler
+    /// dispatch call sites this replaced plain `println!` at
+    Trace,
+}
+
+impl LogLevel {
+    /// the ANSI SGR color code this level's line is wrapped in
+    fn ansi_color(self) -> u8 {
+        match self {
+            LogLevel::Error => 31, // red
+            LogLevel::Warn => 93,  // bright yellow
+            LogLevel::Info => 32,  // green
+            LogLevel::Debug => 34, // blue
+            LogLevel::Trace => 90, // bright black
+        }
+    }
+
+    /// the label printed between the level and the message, e.g. `ERROR`
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+/// the active log level; a call at a more verbose level than this is
+/// filtered out. There's no boot-argument parsing anywhere in this kernel
+/// to read a runtime level from (`rust_main` takes none), so like
+/// [`crate::config::VERBOSE_EXIT_STATS`] this is a plain `pub const` to
+/// flip and recompile. Raise it to [`LogLevel::Trace`] when chasing a
+/// scheduler or timer bug; leave it at [`LogLevel::Info`] for everyday
+/// runs so the per-tick and per-switch `trace!` calls don't drown out
+/// everything else.
+pub const LOG_LEVEL: LogLevel = LogLevel::Info;
+
+/// print one already-formatted log line at `level`, wrapped in its ANSI
+/// color and prefixed with its label
+///
+/// Called only through the macros below, which check `level <= LOG_LEVEL`
+/// before formatting their arguments at all, so this never runs for a
+/// below-threshold call.
+pub fn log_line(level: LogLevel, args: fmt::Arguments) {
+    println!(
+        "\u{1B}[{}m[{}] {}\u{1B}[0m",
+        level.ansi_color(),
+        level.label(),
+        args
+    );
+}
+
+/// log at [`LogLevel::Error`]
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        if $crate::log::LogLevel::Error <= $crate::log::LOG_LEVEL {
+            $crate::log::log_line($crate::log::LogLevel::Error, format_args!($($arg)*));
+        }
+    };
+}
+
+/// log at [`LogLevel::Warn`]
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        if $crate::log::LogLevel::Warn <= $crate::log::LOG_LEVEL {
+            $crate::log::log_line($crate::log::LogLevel::Warn, format_args!($($arg)*));
+        }
+    };
+}
+
+/// log at [`LogLevel::Info`]
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::LogLevel::Info <= $crate::log::LOG_LEVEL {
+            $crate::log::log_line($crate::log::LogLevel::Info, format_args!($($arg)*));
+        }
+    };
+}
+
+/// log at [`LogLevel::Debug`]
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::LogLevel::Debug <= $crate::log::LOG_LEVEL {
+            $crate::log::log_line($crate::log::LogLevel::Debug, format_args!($($arg)*));
+        }
+    };
+}
+
+/// log at [`LogLevel::Trace`]
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::log::LogLevel::Trace <= $crate::log::LOG_LEVEL {
+            $crate::log::log_line($crate::log::LogLevel::Trace, format_args!($($arg)*));
+        }
+    };
+}
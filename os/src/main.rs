@@ -0,0 +1,72 @@
+//! The main module and entrypoint
+//!
+//! This crate implements a batch/multiprogramming RISC-V kernel that runs
+//! applications linked into its image one at a time (or, with the timer
+//! interrupt enabled below, time-slices between the ones that are ready).
+//!
+//! The operating system also starts in this module. Kernel code starts
+//! executing from `entry.asm`, after which [`rust_main()`] clears the BSS
+//! segment, initializes the trap vector and timer, loads the applications,
+//! and finally runs the first task.
+
+#![deny(missing_docs)]
+#![deny(warnings)]
+#![no_main]
+#![no_std]
+#![feature(panic_info_message)]
+#![feature(alloc_error_handler)]
+
+use core::arch::global_asm;
+
+#[macro_use]
+mod console;
+mod config;
+mod fs;
+mod hart;
+mod lang_items;
+mod loader;
+#[macro_use]
+mod log;
+pub mod mm;
+mod rng;
+mod sbi;
+mod stack_trace;
+mod sync;
+mod symtab;
+pub mod syscall;
+pub mod task;
+mod timer;
+pub mod trap;
+mod uart;
+
+global_asm!(include_str!("entry.asm"));
+global_asm!(include_str!("link_app.S"));
+
+fn clear_bss() {
+    extern "C" {
+        fn sbss();
+        fn ebss();
+    }
+    (sbss as usize..ebss as usize).for_each(|a| unsafe { (a as *mut u8).write_volatile(0) });
+}
+
+#[no_mangle]
+fn rust_main() -> ! {
+    clear_bss();
+    println!("[kernel] Hello, world!");
+    // No task has been dispatched on this hart yet, so
+    // `task::current_task_if_live` must read it as "no live current task"
+    // rather than mistaking the zero-initialized slot for task 0 actually
+    // running; this is the pre-init path
+    // `syscall::TotalTasks::add_syscall_times` (and friends) rely on being
+    // a safe no-op for, checked here since this crate has nothing resembling
+    // the sibling `user` crate's test harness to exercise it from.
+    debug_assert!(task::current_task_if_live().is_none());
+    trap::init();
+    trap::enable_fpu();
+    loader::load_apps();
+    trap::enable_timer_interrupt();
+    timer::set_next_trigger();
+    task::run_first_task();
+    panic!("Unreachable in rust_main!");
+}
@@ -21,6 +21,24 @@ const SYSCALL_GET_TIME: usize = 169;
 /// taskinfo syscall
 const SYSCALL_TASK_INFO: usize = 410;
 
+/// the syscall ids we keep per-task statistics for; real syscall ids are
+/// sparse and large (e.g. `SYSCALL_TASK_INFO` is 410), so instead of
+/// indexing `call_time` directly by id we only track this fixed registry
+const SYSCALL_IDS: [usize; 5] = [
+    SYSCALL_WRITE,
+    SYSCALL_EXIT,
+    SYSCALL_YIELD,
+    SYSCALL_GET_TIME,
+    SYSCALL_TASK_INFO,
+];
+/// number of syscalls tracked in [`SYSCALL_IDS`]
+const SYSCALL_NUM: usize = SYSCALL_IDS.len();
+
+/// look up the `call_time` slot for a syscall id, if it is tracked
+fn syscall_index(syscall_id: usize) -> Option<usize> {
+    SYSCALL_IDS.iter().position(|&id| id == syscall_id)
+}
+
 mod fs;
 mod process;
 
@@ -32,7 +50,7 @@ use process::*;
 use crate::config::{
         MAX_APP_NUM,
         MAX_SYSCALL_NUM};
-use crate::task::TASK_MANAGER;
+use crate::task::{TaskStatus, TASK_MANAGER};
 /// handle syscall exception with `syscall_id` and other arguments
 #[no_mangle]
 pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
@@ -47,7 +65,7 @@ pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
         },
         SYSCALL_YIELD => {sys_yield()},
         SYSCALL_GET_TIME => {sys_get_time(args[0] as *mut TimeVal, args[1])},
-        SYSCALL_TASK_INFO => {sys_task_info(args[0] as *mut TaskInfo)},
+        SYSCALL_TASK_INFO => {sys_task_info(args[0], args[1] as *mut TaskInfo)},
         _ => {panic!("Unsupported syscall_id: {}", syscall_id)},
     }
 }
@@ -57,7 +75,7 @@ lazy_static! {
     TotalTasks{
             inner:UPSafeCell::new([
                 TaskStatBlock{
-                    call_time:[0;MAX_SYSCALL_NUM],
+                    call_time:[0;SYSCALL_NUM],
                     start_time:0
                 };
                 MAX_APP_NUM])
@@ -74,7 +92,7 @@ pub struct TotalTasks{
 /// task的系统调用和开始时间统计
 #[derive(Copy,Clone)]
 pub struct TaskStatBlock{
-    call_time:[u32;MAX_SYSCALL_NUM],
+    call_time:[u32;SYSCALL_NUM],
     start_time:usize
 }
 
@@ -109,24 +127,56 @@ impl TotalTasks {
         let tasks = self.inner.exclusive_access();
         get_time_ms() - tasks[current_task].get_task_time()
     }
+    /// 获取任意id对应app的状态、系统调用次数和运行时间，越界或该槽位没有加载app（UnInit）返回None
+    pub fn get_task_info(&self,id:usize)->Option<(TaskStatus,[u32;MAX_SYSCALL_NUM],usize)>{
+        if id >= MAX_APP_NUM {
+            return None;
+        }
+        // `TaskStatus::UnInit` marks a slot with no app loaded in it; a
+        // loaded app that has not been dispatched yet is `Ready` and is
+        // reported too, with `start_time` still 0, so its `time` is the
+        // milliseconds since boot
+        let status = TASK_MANAGER.get_task_status(id);
+        if status == TaskStatus::UnInit {
+            return None;
+        }
+        let tasks = self.inner.exclusive_access();
+        let task = &tasks[id];
+        Some((
+            status,
+            task.get_total_syscall_times(),
+            get_time_ms() - task.get_task_time(),
+        ))
+    }
 }
 
 impl TaskStatBlock {
-    /// 获取当前app的全部syscall的次数
+    /// 获取当前app的全部syscall的次数，按照真实的syscall id展开成外部可见的视图
     pub fn get_total_syscall_times(&self)->[u32;MAX_SYSCALL_NUM]{
-        self.call_time.clone()
+        let mut times = [0; MAX_SYSCALL_NUM];
+        for (slot, &id) in self.call_time.iter().zip(SYSCALL_IDS.iter()) {
+            times[id] = *slot;
+        }
+        times
     }
-    /// 递增syscall次数
+    /// 递增syscall次数，未被统计的syscall id会被忽略
     pub fn add_syscall_times(&mut self,syscall_id:usize){
-        self.call_time[syscall_id]+=1;
+        if let Some(idx) = syscall_index(syscall_id) {
+            self.call_time[idx] += 1;
+        }
     }
-    /// 获取syscall次数
+    /// 获取syscall次数，未被统计的syscall id返回0
     pub fn get_syscall_times(&self,syscall_id:usize)->u32{
-        self.call_time[syscall_id]
+        syscall_index(syscall_id).map_or(0, |idx| self.call_time[idx])
     }
-    /// 开始计时
+    /// 记录第一次被调度的时刻，之后的调度（挂起再恢复）不会覆盖它。
+    /// 运行时长以`get_time_ms() - start_time`计算，即从第一次调度起
+    /// 经过的墙上时间：被定时器中断抢占、挂起期间（其他task在运行）
+    /// 的时间也计算在内，并不只累计该task自己实际运行的区间。
     pub fn start_task_time(&mut self){
-        self.start_time = get_time_ms()
+        if self.start_time == 0 {
+            self.start_time = get_time_ms()
+        }
     }
     /// 获取开始时间
     pub fn get_task_time(&self)->usize{
@@ -0,0 +1,17 @@
+//! Address types
+
+/// the size in bytes of a page, and the granularity `translated_write`
+/// assumes user buffers may be split across
+pub const PAGE_SIZE: usize = 0x1000;
+
+/// a virtual address
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VirtAddr(pub usize);
+
+impl VirtAddr {
+    /// the address one past the end of this address's page, i.e. the start
+    /// of the next page
+    pub fn ceil_page(self) -> VirtAddr {
+        VirtAddr((self.0 + PAGE_SIZE) & !(PAGE_SIZE - 1))
+    }
+}
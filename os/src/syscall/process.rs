@@ -0,0 +1,77 @@
+//! Process-related syscalls
+
+use super::TOTAL_TASKS;
+use crate::config::MAX_SYSCALL_NUM;
+use crate::task::{exit_current_and_run_next, suspend_current_and_run_next, TaskStatus};
+use crate::timer::get_time_ms;
+
+#[repr(C)]
+#[derive(Debug)]
+/// Time value returned by `sys_get_time`
+pub struct TimeVal {
+    /// seconds
+    pub sec: usize,
+    /// microseconds
+    pub usec: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// Task information returned by `sys_task_info`
+pub struct TaskInfo {
+    /// Status of the task
+    pub status: TaskStatus,
+    /// The number of times each syscall has been called by the task
+    pub syscall_times: [u32; MAX_SYSCALL_NUM],
+    /// Milliseconds elapsed since the task's first dispatch, including time
+    /// it spent suspended while other tasks ran
+    pub time: usize,
+}
+
+/// task exits and submit an exit code
+pub fn sys_exit(exit_code: i32) -> ! {
+    exit_current_and_run_next(exit_code);
+    unreachable!("Unreachable in sys_exit!")
+}
+
+/// current task gives up resources for other tasks
+pub fn sys_yield() -> isize {
+    suspend_current_and_run_next();
+    0
+}
+
+/// get current time in milliseconds
+pub fn sys_get_time(ts: *mut TimeVal, _tz: usize) -> isize {
+    if ts.is_null() {
+        return -1;
+    }
+    let ms = get_time_ms();
+    unsafe {
+        *ts = TimeVal {
+            sec: ms / 1000,
+            usec: (ms % 1000) * 1000,
+        };
+    }
+    0
+}
+
+/// get the status, syscall histogram and running time of the task
+/// identified by `id`
+pub fn sys_task_info(id: usize, ti: *mut TaskInfo) -> isize {
+    if ti.is_null() {
+        return -1;
+    }
+    match TOTAL_TASKS.get_task_info(id) {
+        Some((status, syscall_times, time)) => {
+            unsafe {
+                *ti = TaskInfo {
+                    status,
+                    syscall_times,
+                    time,
+                };
+            }
+            0
+        }
+        None => -1,
+    }
+}
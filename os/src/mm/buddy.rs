@@ -0,0 +1,353 @@
+//! A buddy-system allocator for contiguous, power-of-two runs of pages
+//!
+//! This kernel has no frame allocator over real physical RAM yet — every
+//! app still lives in a fixed, statically reserved slot handed out by
+//! [`crate::loader`], and [`crate::mm`]'s own module doc explains why a
+//! real one hasn't been built. [`PhysPageNum`] here is therefore an index
+//! into a small statically reserved arena of this module's own, the same
+//! workaround [`crate::task::SHM_PAGES`] uses for shared memory, not an
+//! index into all of physical memory. It's enough to give contiguous
+//! multi-page allocation a real home, ready to be pointed at the rest of
+//! RAM once a true frame allocator exists.
+//!
+//! Debug builds additionally track each arena page's free/allocated state
+//! and poison-fill a page's bytes as soon as it's freed (see
+//! [`BuddyState::frame_state`]), so a double [`free_contiguous`] panics
+//! with the offending page index instead of silently corrupting a free
+//! list, and a stray read through an already-freed pointer comes back
+//! looking deliberately wrong rather than merely stale. `#[cfg(debug_assertions)]`
+//! throughout, so none of it costs anything in a release build.
+
+use super::PAGE_SIZE;
+use crate::sync::SpinLock;
+use lazy_static::lazy_static;
+
+/// the largest block size this allocator hands out, as a power of two
+/// number of pages; `1 << MAX_ORDER` pages make up the whole arena
+const MAX_ORDER: usize = 6;
+
+/// how many pages the arena holds; sized for a handful of order-3 (8-page)
+/// allocations at once, which is all this teaching kernel has ever needed
+const ARENA_PAGES: usize = 1 << MAX_ORDER;
+
+/// sentinel meaning "no page", since page index `0` is a valid arena slot
+const NONE: usize = usize::MAX;
+
+#[repr(align(4096))]
+#[derive(Copy, Clone)]
+struct ArenaPage {
+    data: [u8; PAGE_SIZE],
+}
+
+/// the buddy allocator's statically reserved backing storage; see this
+/// module's own doc comment for why it's a fixed arena rather than all of
+/// physical RAM
+static ARENA: [ArenaPage; ARENA_PAGES] = [ArenaPage {
+    data: [0; PAGE_SIZE],
+}; ARENA_PAGES];
+
+/// an index into [`ARENA`]; see this module's doc comment for why this is
+/// not a real physical address
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PhysPageNum(pub usize);
+
+impl PhysPageNum {
+    /// the identity-mapped virtual address backing this page, usable the
+    /// same way any other address in this kernel's flat address space is
+    pub fn addr(self) -> usize {
+        ARENA.as_ptr() as usize + self.0 * PAGE_SIZE
+    }
+}
+
+fn write_next(page: usize, next: usize) {
+    let addr = ARENA.as_ptr() as usize + page * PAGE_SIZE;
+    unsafe {
+        (addr as *mut usize).write_volatile(next);
+    }
+}
+
+fn read_next(page: usize) -> usize {
+    let addr = ARENA.as_ptr() as usize + page * PAGE_SIZE;
+    unsafe { (addr as *const usize).read_volatile() }
+}
+
+/// a single arena page's allocation state, tracked only in debug builds;
+/// see [`BuddyState::frame_state`]
+#[cfg(debug_assertions)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum FrameState {
+    Free,
+    Allocated,
+}
+
+/// the byte every page is filled with as soon as it's freed, so a stray
+/// read through a stale pointer after `free_contiguous` comes back as an
+/// unmistakable pattern instead of whatever the next allocation happened to
+/// leave behind; debug builds only, same as `frame_state` itself
+#[cfg(debug_assertions)]
+const POISON_BYTE: u8 = 0xCC;
+
+struct BuddyState {
+    /// `free_heads[order]` is the arena index of the first free block of
+    /// that order, chained through [`write_next`]/[`read_next`] stored in
+    /// each free block's own first word, or [`NONE`] if that order's free
+    /// list is empty
+    free_heads: [usize; MAX_ORDER + 1],
+    /// the order of the free block starting at page `p`, valid only while
+    /// `p` is actually the head of one of `free_heads`' chains; used on
+    /// free to tell whether a buddy is currently free and at the same
+    /// order, without walking every free list to find out
+    block_order: [u8; ARENA_PAGES],
+    /// whether page `p` is currently the start of a free block; see
+    /// `block_order`
+    is_free_start: [bool; ARENA_PAGES],
+    /// per-page free/allocated state, checked by [`BuddyState::free`] to
+    /// catch a double-free before it corrupts a free list that already
+    /// holds the page; `#[cfg(debug_assertions)]` so a release build pays
+    /// nothing for it, the same way `debug_assert!` would
+    #[cfg(debug_assertions)]
+    frame_state: [FrameState; ARENA_PAGES],
+}
+
+impl BuddyState {
+    fn new() -> Self {
+        let mut free_heads = [NONE; MAX_ORDER + 1];
+        free_heads[MAX_ORDER] = 0;
+        let mut block_order = [0u8; ARENA_PAGES];
+        block_order[0] = MAX_ORDER as u8;
+        let mut is_free_start = [false; ARENA_PAGES];
+        is_free_start[0] = true;
+        Self {
+            free_heads,
+            block_order,
+            is_free_start,
+            #[cfg(debug_assertions)]
+            frame_state: [FrameState::Free; ARENA_PAGES],
+        }
+    }
+
+    /// fill every page in the `1 << order`-page block starting at `page`
+    /// with [`POISON_BYTE`]; debug builds only
+    #[cfg(debug_assertions)]
+    fn poison(page: usize, order: usize) {
+        for p in page..page + (1 << order) {
+            let addr = ARENA.as_ptr() as usize + p * PAGE_SIZE;
+            unsafe {
+                core::ptr::write_bytes(addr as *mut u8, POISON_BYTE, PAGE_SIZE);
+            }
+        }
+    }
+
+    fn list_pop(&mut self, order: usize) -> Option<usize> {
+        let page = self.free_heads[order];
+        if page == NONE {
+            return None;
+        }
+        self.free_heads[order] = read_next(page);
+        self.is_free_start[page] = false;
+        Some(page)
+    }
+
+    fn list_push(&mut self, order: usize, page: usize) {
+        write_next(page, self.free_heads[order]);
+        self.free_heads[order] = page;
+        self.block_order[page] = order as u8;
+        self.is_free_start[page] = true;
+    }
+
+    /// remove `page` from the middle of its own order's free list; used
+    /// when its buddy (not necessarily the list head) turns out to be free
+    /// and about to be merged with it
+    fn list_remove(&mut self, order: usize, page: usize) {
+        let mut cursor = self.free_heads[order];
+        let mut prev = NONE;
+        while cursor != NONE {
+            let next = read_next(cursor);
+            if cursor == page {
+                if prev == NONE {
+                    self.free_heads[order] = next;
+                } else {
+                    write_next(prev, next);
+                }
+                self.is_free_start[page] = false;
+                return;
+            }
+            prev = cursor;
+            cursor = next;
+        }
+    }
+
+    fn alloc(&mut self, order: usize) -> Option<usize> {
+        // the single-frame fast path (order 0, the common case) falls out
+        // of the same loop below at zero extra cost when a block is
+        // already free at exactly that order: no split happens, just one
+        // list_pop
+        let found_order = (order..=MAX_ORDER).find(|&o| self.free_heads[o] != NONE)?;
+        let mut page = self.list_pop(found_order)?;
+        let mut current_order = found_order;
+        while current_order > order {
+            current_order -= 1;
+            let buddy = page + (1 << current_order);
+            self.list_push(current_order, buddy);
+        }
+        #[cfg(debug_assertions)]
+        for p in page..page + (1 << order) {
+            self.frame_state[p] = FrameState::Allocated;
+        }
+        Some(page)
+    }
+
+    fn free(&mut self, mut page: usize, mut order: usize) {
+        // catch a double-free (or a free of a page this allocator never
+        // handed out) before anything below touches the free lists — doing
+        // it after `list_push` would silently corrupt a chain that already
+        // has this page in it
+        #[cfg(debug_assertions)]
+        for p in page..page + (1 << order) {
+            if self.frame_state[p] == FrameState::Free {
+                panic!(
+                    "double free: ppn {} (freeing {}..{} at order {})",
+                    p,
+                    page,
+                    page + (1 << order),
+                    order
+                );
+            }
+        }
+        #[cfg(debug_assertions)]
+        {
+            Self::poison(page, order);
+            for p in page..page + (1 << order) {
+                self.frame_state[p] = FrameState::Free;
+            }
+        }
+        while order < MAX_ORDER {
+            let buddy = page ^ (1 << order);
+            if self.is_free_start[buddy] && self.block_order[buddy] as usize == order {
+                self.list_remove(order, buddy);
+                page = page.min(buddy);
+                order += 1;
+            } else {
+                break;
+            }
+        }
+        self.list_push(order, page);
+    }
+}
+
+/// the kernel-wide buddy allocator; see this module's own doc comment
+pub struct BuddyAllocator {
+    inner: SpinLock<BuddyState>,
+}
+
+impl BuddyAllocator {
+    /// allocate `1 << order` contiguous pages, or `None` if the arena has
+    /// no free block that large
+    pub fn alloc_contiguous(&self, order: usize) -> Option<PhysPageNum> {
+        if order > MAX_ORDER {
+            return None;
+        }
+        self.inner.exclusive_access().alloc(order).map(PhysPageNum)
+    }
+
+    /// return a block previously handed out by [`alloc_contiguous`] for
+    /// `order`, coalescing it with its buddy (and that buddy's buddy, and
+    /// so on) wherever doing so is possible
+    ///
+    /// [`alloc_contiguous`]: Self::alloc_contiguous
+    pub fn free_contiguous(&self, ppn: PhysPageNum, order: usize) {
+        self.inner.exclusive_access().free(ppn.0, order);
+    }
+
+    /// `(total_pages, free_pages)` in the arena; see
+    /// [`SysInfo::total_frames`](crate::syscall::process::SysInfo::total_frames)
+    pub fn frame_counts(&self) -> (usize, usize) {
+        let inner = self.inner.exclusive_access();
+        let free = inner
+            .free_heads
+            .iter()
+            .enumerate()
+            .map(|(order, &head)| {
+                let mut count = 0;
+                let mut cursor = head;
+                while cursor != NONE {
+                    count += 1;
+                    cursor = read_next(cursor);
+                }
+                count * (1 << order)
+            })
+            .sum();
+        (ARENA_PAGES, free)
+    }
+
+    /// call `f` once for every arena page not currently free, in ascending
+    /// page-index order; used by `sys_shutdown`'s leak report
+    /// ([`crate::syscall::process::sys_shutdown`]).
+    ///
+    /// There's no per-page owner tag anywhere in this allocator — only
+    /// `frame_state` exists, and only in debug builds, tracking free-vs-
+    /// allocated with nothing about who allocated it (see this module's own
+    /// doc comment on why there's no real frame allocator to own that
+    /// bookkeeping yet). So a leak report built on this can only ever name
+    /// the leaked page index, never the subsystem that forgot to free it.
+    pub fn for_each_allocated(&self, mut f: impl FnMut(usize)) {
+        let inner = self.inner.exclusive_access();
+        let mut free = [false; ARENA_PAGES];
+        for (order, &head) in inner.free_heads.iter().enumerate() {
+            let mut cursor = head;
+            while cursor != NONE {
+                for p in cursor..cursor + (1 << order) {
+                    free[p] = true;
+                }
+                cursor = read_next(cursor);
+            }
+        }
+        for (p, &is_free) in free.iter().enumerate() {
+            if !is_free {
+                f(p);
+            }
+        }
+    }
+}
+
+lazy_static! {
+    /// the single kernel-wide instance of [`BuddyAllocator`]
+    pub static ref FRAME_ALLOCATOR: BuddyAllocator = BuddyAllocator {
+        inner: SpinLock::new(BuddyState::new()),
+    };
+}
+
+/// allocate `1 << order` contiguous pages; see
+/// [`BuddyAllocator::alloc_contiguous`]
+///
+/// A unit test allocating an order-3 block, checking its eight pages are
+/// contiguous, freeing it, and re-allocating to confirm it coalesced back
+/// down — or, for [`free_contiguous`], freeing the same block twice and
+/// asserting the `"double free"` panic fires — would fit naturally as a
+/// `#[cfg(test)]` module right in this file — unlike the syscall-facing
+/// tests elsewhere in this kernel, it wouldn't need a binary in the sibling
+/// `user` crate at all. But this crate is built `#![no_std]`/`#![no_main]`
+/// for a bare-metal target with no host test harness wired up anywhere in
+/// this source tree (no `[[test]]` target, no `std`-based simulation of the
+/// allocator), so there's nowhere for one to actually run.
+pub fn alloc_contiguous(order: usize) -> Option<PhysPageNum> {
+    FRAME_ALLOCATOR.alloc_contiguous(order)
+}
+
+/// free a block previously returned by [`alloc_contiguous`]; see
+/// [`BuddyAllocator::free_contiguous`]
+pub fn free_contiguous(ppn: PhysPageNum, order: usize) {
+    FRAME_ALLOCATOR.free_contiguous(ppn, order)
+}
+
+/// `(total_pages, free_pages)` in the arena; see
+/// [`BuddyAllocator::frame_counts`]
+pub fn frame_counts() -> (usize, usize) {
+    FRAME_ALLOCATOR.frame_counts()
+}
+
+/// call `f` once for every arena page still allocated; see
+/// [`BuddyAllocator::for_each_allocated`]
+pub fn for_each_allocated(f: impl FnMut(usize)) {
+    FRAME_ALLOCATOR.for_each_allocated(f)
+}
@@ -0,0 +1,146 @@
+//! Task management implementation
+//!
+//! Everything about task scheduling is encapsulated in this module. A task
+//! is identified solely by its index into the app list; [`TaskManager`] owns
+//! the array of [`TaskControlBlock`]s and the currently running task's
+//! index, and is the only thing [`crate::syscall`] and [`crate::trap`] talk
+//! to when they need to suspend, exit, or inspect a task.
+
+mod context;
+mod switch;
+mod task;
+
+use crate::config::MAX_APP_NUM;
+use crate::loader::{get_num_app, init_app_cx};
+use crate::sync::UPSafeCell;
+use crate::syscall::TOTAL_TASKS;
+use context::TaskContext;
+use lazy_static::lazy_static;
+use switch::__switch;
+pub use task::{TaskControlBlock, TaskStatus};
+
+/// the task manager, where all the tasks are managed
+pub struct TaskManager {
+    /// total number of tasks
+    num_app: usize,
+    /// writable task-manager-wide state
+    inner: UPSafeCell<TaskManagerInner>,
+}
+
+struct TaskManagerInner {
+    tasks: [TaskControlBlock; MAX_APP_NUM],
+    current_task: usize,
+}
+
+lazy_static! {
+    /// a global instance through which we can operate all tasks
+    pub static ref TASK_MANAGER: TaskManager = {
+        let num_app = get_num_app();
+        let mut tasks = [TaskControlBlock {
+            task_cx: TaskContext::zero_init(),
+            task_status: TaskStatus::UnInit,
+        }; MAX_APP_NUM];
+        for (i, task) in tasks.iter_mut().enumerate().take(num_app) {
+            task.task_cx = TaskContext::goto_restore(init_app_cx(i));
+            task.task_status = TaskStatus::Ready;
+        }
+        TaskManager {
+            num_app,
+            inner: unsafe {
+                UPSafeCell::new(TaskManagerInner {
+                    tasks,
+                    current_task: 0,
+                })
+            },
+        }
+    };
+}
+
+impl TaskManager {
+    /// run the first task in the list
+    fn run_first_task(&self) -> ! {
+        let mut inner = self.inner.exclusive_access();
+        let task0 = &mut inner.tasks[0];
+        task0.task_status = TaskStatus::Running;
+        let next_task_cx_ptr = &task0.task_cx as *const TaskContext;
+        drop(inner);
+        TOTAL_TASKS.start_current_task_time();
+        let mut unused = TaskContext::zero_init();
+        unsafe {
+            __switch(&mut unused as *mut TaskContext, next_task_cx_ptr);
+        }
+        unreachable!();
+    }
+
+    /// mark the current task as ready to run again
+    fn mark_current_suspended(&self) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_task;
+        inner.tasks[current].task_status = TaskStatus::Ready;
+    }
+
+    /// mark the current task as exited
+    fn mark_current_exited(&self) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_task;
+        inner.tasks[current].task_status = TaskStatus::Exited;
+    }
+
+    /// find the next task that is ready to run, in round-robin order
+    fn find_next_task(&self) -> Option<usize> {
+        let inner = self.inner.exclusive_access();
+        let current = inner.current_task;
+        (current + 1..current + self.num_app + 1)
+            .map(|id| id % self.num_app)
+            .find(|id| inner.tasks[*id].task_status == TaskStatus::Ready)
+    }
+
+    /// switch to the next ready task, or shut down if there is none
+    fn run_next_task(&self) {
+        if let Some(next) = self.find_next_task() {
+            let mut inner = self.inner.exclusive_access();
+            let current = inner.current_task;
+            inner.tasks[next].task_status = TaskStatus::Running;
+            inner.current_task = next;
+            let current_task_cx_ptr = &mut inner.tasks[current].task_cx as *mut TaskContext;
+            let next_task_cx_ptr = &inner.tasks[next].task_cx as *const TaskContext;
+            drop(inner);
+            TOTAL_TASKS.start_current_task_time();
+            unsafe {
+                __switch(current_task_cx_ptr, next_task_cx_ptr);
+            }
+        } else {
+            println!("All applications completed!");
+            crate::sbi::shutdown(false);
+        }
+    }
+
+    /// get the id of the currently running task
+    pub fn get_current_task(&self) -> usize {
+        self.inner.exclusive_access().current_task
+    }
+
+    /// get the status of an arbitrary task by id, used by
+    /// `sys_task_info` to answer queries about tasks other than the
+    /// current one
+    pub fn get_task_status(&self, id: usize) -> TaskStatus {
+        self.inner.exclusive_access().tasks[id].task_status
+    }
+}
+
+/// run the first task
+pub fn run_first_task() {
+    TASK_MANAGER.run_first_task();
+}
+
+/// suspend the current task and run the next one
+pub fn suspend_current_and_run_next() {
+    TASK_MANAGER.mark_current_suspended();
+    TASK_MANAGER.run_next_task();
+}
+
+/// exit the current task and run the next one
+pub fn exit_current_and_run_next(_exit_code: i32) {
+    TASK_MANAGER.mark_current_exited();
+    TASK_MANAGER.run_next_task();
+}
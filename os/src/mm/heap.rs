@@ -0,0 +1,251 @@
+//! The kernel heap: a hand-rolled boundary-tag allocator wired up as this
+//! crate's `#[global_allocator]`
+//!
+//! Nothing in this kernel used `alloc::boxed::Box`/`Vec`/etc. before this,
+//! so there was no heap allocator at all — [`crate::config::KERNEL_HEAP_SIZE`]
+//! was a leftover constant with nothing reading it. This gives it a home:
+//! a single static arena, carved up first-fit with boundary tags so
+//! adjacent free blocks on either side of a freed one can be coalesced
+//! back together, the same fragmentation concern [`super::buddy`] exists
+//! for at page granularity. [`HeapStats`] tracks the few atomic counters
+//! `sys_heapinfo` (in `crate::syscall::process`) reports.
+
+use crate::config::KERNEL_HEAP_SIZE;
+use crate::sync::SpinLock;
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::size_of;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+const WORD: usize = size_of::<usize>();
+/// a free block's own first word doubles as its free-list `next` pointer,
+/// so a payload can never be smaller than this
+const MIN_PAYLOAD: usize = WORD;
+/// bytes of bookkeeping (one header word, one footer word) surrounding
+/// every block, used or free
+const OVERHEAD: usize = 2 * WORD;
+const FREE_BIT: usize = 1;
+const NONE: usize = usize::MAX;
+
+fn round_up(size: usize) -> usize {
+    (size.max(MIN_PAYLOAD) + WORD - 1) / WORD * WORD
+}
+
+#[repr(align(8))]
+struct HeapArena([u8; KERNEL_HEAP_SIZE]);
+
+static HEAP: HeapArena = HeapArena([0; KERNEL_HEAP_SIZE]);
+
+fn heap_base() -> usize {
+    HEAP.0.as_ptr() as usize
+}
+
+fn heap_end() -> usize {
+    heap_base() + KERNEL_HEAP_SIZE
+}
+
+fn header_addr(payload: usize) -> usize {
+    payload - WORD
+}
+
+fn footer_addr(payload: usize, size: usize) -> usize {
+    payload + size
+}
+
+unsafe fn read_word(addr: usize) -> usize {
+    (addr as *const usize).read_volatile()
+}
+
+unsafe fn write_word(addr: usize, value: usize) {
+    (addr as *mut usize).write_volatile(value);
+}
+
+unsafe fn write_header(payload: usize, size: usize, free: bool) {
+    write_word(header_addr(payload), size | if free { FREE_BIT } else { 0 });
+    write_word(footer_addr(payload, size), size);
+}
+
+unsafe fn read_header(payload: usize) -> (usize, bool) {
+    let raw = read_word(header_addr(payload));
+    (raw & !FREE_BIT, raw & FREE_BIT != 0)
+}
+
+struct HeapState {
+    /// head of the free-block list, chained through each free block's own
+    /// first payload word, or [`NONE`] if empty
+    free_head: usize,
+}
+
+impl HeapState {
+    fn new() -> Self {
+        let base = heap_base();
+        let payload = base + WORD;
+        let size = KERNEL_HEAP_SIZE - OVERHEAD;
+        unsafe {
+            write_header(payload, size, true);
+            write_word(payload, NONE);
+        }
+        Self { free_head: payload }
+    }
+
+    fn list_remove(&mut self, payload: usize) {
+        let mut cursor = self.free_head;
+        let mut prev = NONE;
+        while cursor != NONE {
+            let next = unsafe { read_word(cursor) };
+            if cursor == payload {
+                if prev == NONE {
+                    self.free_head = next;
+                } else {
+                    unsafe { write_word(prev, next) };
+                }
+                return;
+            }
+            prev = cursor;
+            cursor = next;
+        }
+    }
+
+    fn list_push(&mut self, payload: usize) {
+        unsafe { write_word(payload, self.free_head) };
+        self.free_head = payload;
+    }
+
+    fn alloc(&mut self, required: usize) -> Option<usize> {
+        let mut cursor = self.free_head;
+        while cursor != NONE {
+            let (size, _free) = unsafe { read_header(cursor) };
+            let next = unsafe { read_word(cursor) };
+            if size >= required {
+                self.list_remove(cursor);
+                if size - required >= OVERHEAD + MIN_PAYLOAD {
+                    let used_size = required;
+                    let remainder_payload = cursor + used_size + OVERHEAD;
+                    let remainder_size = size - used_size - OVERHEAD;
+                    unsafe {
+                        write_header(cursor, used_size, false);
+                        write_header(remainder_payload, remainder_size, true);
+                    }
+                    self.list_push(remainder_payload);
+                } else {
+                    unsafe { write_header(cursor, size, false) };
+                }
+                return Some(cursor);
+            }
+            cursor = next;
+        }
+        None
+    }
+
+    fn free(&mut self, mut payload: usize) {
+        let (mut size, _) = unsafe { read_header(payload) };
+        unsafe { write_header(payload, size, true) };
+
+        let next_header = footer_addr(payload, size) + WORD;
+        if next_header < heap_end() {
+            let next_payload = next_header + WORD;
+            let (next_size, next_free) = unsafe { read_header(next_payload) };
+            if next_free {
+                self.list_remove(next_payload);
+                size += OVERHEAD + next_size;
+                unsafe { write_header(payload, size, true) };
+            }
+        }
+
+        if header_addr(payload) > heap_base() {
+            let left_footer = header_addr(payload) - WORD;
+            let prev_size = unsafe { read_word(left_footer) };
+            let prev_payload = left_footer - prev_size;
+            let (_, prev_free) = unsafe { read_header(prev_payload) };
+            if prev_free {
+                self.list_remove(prev_payload);
+                size += OVERHEAD + prev_size;
+                payload = prev_payload;
+                unsafe { write_header(payload, size, true) };
+            }
+        }
+
+        self.list_push(payload);
+    }
+}
+
+/// the few atomic counters [`sys_heapinfo`](crate::syscall::process::sys_heapinfo)
+/// reports; each alloc/dealloc touches at most two of these, and only with
+/// plain atomic ops — no lock
+pub struct HeapStats {
+    /// bytes currently handed out and not yet freed
+    allocated: AtomicUsize,
+    /// the largest `allocated` has ever been
+    peak: AtomicUsize,
+    /// how many allocations are currently outstanding (not yet freed)
+    live_count: AtomicUsize,
+}
+
+static STATS: HeapStats = HeapStats {
+    allocated: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+    live_count: AtomicUsize::new(0),
+};
+
+impl HeapStats {
+    /// `(total_size, allocated, peak, live_count)`, for `sys_heapinfo`
+    pub fn snapshot() -> (usize, usize, usize, usize) {
+        (
+            KERNEL_HEAP_SIZE,
+            STATS.allocated.load(Ordering::Relaxed),
+            STATS.peak.load(Ordering::Relaxed),
+            STATS.live_count.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// the allocator this crate's `#[global_allocator]` resolves to; see this
+/// module's own doc comment
+pub struct KernelHeap {
+    inner: SpinLock<Option<HeapState>>,
+}
+
+unsafe impl GlobalAlloc for KernelHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.align() > WORD {
+            return core::ptr::null_mut();
+        }
+        let required = round_up(layout.size());
+        let mut state = self.inner.exclusive_access();
+        if state.is_none() {
+            *state = Some(HeapState::new());
+        }
+        match state.as_mut().unwrap().alloc(required) {
+            Some(payload) => {
+                let allocated = STATS.allocated.fetch_add(required, Ordering::Relaxed) + required;
+                STATS.peak.fetch_max(allocated, Ordering::Relaxed);
+                STATS.live_count.fetch_add(1, Ordering::Relaxed);
+                payload as *mut u8
+            }
+            None => core::ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let required = round_up(layout.size());
+        self.inner
+            .exclusive_access()
+            .as_mut()
+            .unwrap()
+            .free(ptr as usize);
+        STATS.allocated.fetch_sub(required, Ordering::Relaxed);
+        STATS.live_count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static KERNEL_HEAP: KernelHeap = KernelHeap {
+    inner: SpinLock::new(None),
+};
+
+/// called when the global allocator returns null; this kernel has no way
+/// to reclaim memory under pressure, so there is nothing to do but report
+/// the failure and stop
+#[alloc_error_handler]
+fn alloc_error_handler(layout: Layout) -> ! {
+    panic!("kernel heap exhausted trying to allocate {:?}", layout);
+}
@@ -0,0 +1,25 @@
+//! Types related to task management
+
+use super::context::TaskContext;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// the execution status of a task
+pub enum TaskStatus {
+    /// not yet initialized
+    UnInit,
+    /// ready to run
+    Ready,
+    /// currently running
+    Running,
+    /// exited
+    Exited,
+}
+
+#[derive(Copy, Clone)]
+/// task control block structure
+pub struct TaskControlBlock {
+    /// the task context, saved and restored by `__switch`
+    pub task_cx: TaskContext,
+    /// the execution status of the task
+    pub task_status: TaskStatus,
+}
@@ -0,0 +1,162 @@
+//! Memory-related helpers
+//!
+//! This kernel does not (yet) give every app its own page table: apps are
+//! still loaded into fixed, statically reserved physical slots (see
+//! [`crate::loader`]). What this module provides today is the seam user
+//! pointers go through on their way into the kernel, so that code calling
+//! into it doesn't care whether the pointer it was handed happens to be
+//! backed by a single contiguous mapping or, once real per-task address
+//! spaces exist, by several non-contiguous frames.
+//!
+//! ASIDs have no meaning to reclaim here. An ASID is a tag `satp` carries so
+//! the hardware can keep multiple address spaces' translations in the TLB at
+//! once and switch between them without a flush; this kernel never writes
+//! `satp` at all — every task shares the same single, flat, identity-mapped
+//! view of physical memory (see [`TaskManager::run_next_task`](crate::task::TaskManager::run_next_task),
+//! whose context switch changes only the stack pointer and callee-saved
+//! registers), so there's no second address space for a miss-on-switch to
+//! even be possible, let alone a TLB to measure misses against. Building
+//! this for real needs per-task page tables and a frame allocator first —
+//! the same prerequisites [`crate::loader`]'s module doc explains are
+//! missing for demand paging — so it isn't attempted here.
+//!
+//! [`buddy`] is a step in that direction, but scoped down to its own small
+//! statically reserved arena rather than real physical RAM — see its own
+//! module doc comment. [`slab`] builds a typed fixed-size-object allocator
+//! on top of it; see its own module doc comment for why nothing calls into
+//! it yet.
+//!
+//! A shared, refcounted frame table for copy-on-write fork also isn't
+//! implementable on top of what's here. Refcounting a frame only pays off
+//! once two tasks can point distinct page-table entries at the same
+//! physical page and fault in a private copy the first time either writes
+//! to it — that needs per-task page tables and a page-fault-driven copy
+//! path, the same two prerequisites this module doc already calls out as
+//! missing. Without them, [`crate::task::TaskManager::fork_current`]'s
+//! eager whole-slot duplication (via [`crate::loader::clone_app_state`])
+//! is the only kind of fork this kernel can do: there's no second mapping
+//! of the same frame for a refcount to ever need to track.
+
+mod address;
+mod buddy;
+mod heap;
+mod slab;
+
+pub use address::{VirtAddr, PAGE_SIZE};
+pub use buddy::{
+    alloc_contiguous, for_each_allocated, frame_counts, free_contiguous, BuddyAllocator,
+    PhysPageNum, FRAME_ALLOCATOR,
+};
+pub use heap::HeapStats;
+pub use slab::SlabCache;
+
+/// the value a pointer-taking syscall returns when asked to touch memory
+/// [`copy_to_user`]/[`copy_from_user`]/[`validate_user_range`] rejects —
+/// Linux's `EFAULT`, negated the way every other syscall error in this
+/// kernel already is (see `DEADLOCK_ERRNO` in `crate::syscall::process`)
+pub const EFAULT: isize = -14;
+
+/// run `f` once per page-aligned chunk of the user-supplied `[ptr, ptr +
+/// len)` range, in order
+///
+/// Today every app's memory is one contiguous, identity-mapped region, so
+/// this never has to stitch together non-contiguous frames; it only needs to
+/// cut the range at page boundaries. Callers should go through this (rather
+/// than dereferencing a raw pointer across the whole range directly) so that
+/// a future page-table backed implementation is a drop-in replacement.
+fn for_each_translated_chunk(ptr: *mut u8, len: usize, mut f: impl FnMut(&mut [u8])) {
+    let start = ptr as usize;
+    let end = start + len;
+    let mut cur = start;
+    while cur < end {
+        let page_end = core::cmp::min(VirtAddr(cur).ceil_page().0, end);
+        let chunk = unsafe { core::slice::from_raw_parts_mut(cur as *mut u8, page_end - cur) };
+        f(chunk);
+        cur = page_end;
+    }
+}
+
+/// write `val` to a (possibly page-boundary-crossing) user pointer
+///
+/// # Safety
+/// `ptr` must point at `size_of::<T>()` bytes of memory the calling task is
+/// allowed to write to.
+pub unsafe fn translated_write<T: Copy>(ptr: *mut T, val: T) {
+    let bytes =
+        core::slice::from_raw_parts(&val as *const T as *const u8, core::mem::size_of::<T>());
+    let mut written = 0;
+    for_each_translated_chunk(ptr as *mut u8, bytes.len(), |chunk| {
+        chunk.copy_from_slice(&bytes[written..written + chunk.len()]);
+        written += chunk.len();
+    });
+}
+
+/// read a `T` out of a (possibly page-boundary-crossing) user pointer
+///
+/// # Safety
+/// `ptr` must point at `size_of::<T>()` bytes of memory the calling task is
+/// allowed to read from.
+pub unsafe fn translated_read<T: Copy>(ptr: *const T) -> T {
+    let mut val = core::mem::MaybeUninit::<T>::uninit();
+    let out = core::slice::from_raw_parts_mut(val.as_mut_ptr() as *mut u8, core::mem::size_of::<T>());
+    let mut read = 0;
+    for_each_translated_chunk(ptr as *mut u8, out.len(), |chunk| {
+        out[read..read + chunk.len()].copy_from_slice(chunk);
+        read += chunk.len();
+    });
+    val.assume_init()
+}
+
+/// whether `[ptr, ptr + len)` is a range the calling task may access —
+/// readable always, and writable too if `want_write` is set
+///
+/// `sys_write` validates a caller-supplied buffer of arbitrary,
+/// syscall-chosen length through this rather than through
+/// [`copy_from_user`]: with no heap allocator anywhere in this kernel, there
+/// is nowhere to copy an arbitrarily-sized buffer into, so it still slices
+/// the user pointer directly after checking it here instead of through an
+/// owned copy.
+///
+/// A caller-chosen `len` large enough to wrap `ptr + len` past
+/// `usize::MAX` can't sneak past this: the underlying
+/// [`crate::task::TaskManager::user_range_permitted`] computes `ptr + len`
+/// with `checked_add` and rejects the range outright on overflow, the same
+/// way it rejects a range that falls outside every one of the calling
+/// task's mapped regions (its loaded program image, its stack, or an
+/// `mmap`ed area) with the needed permission bit. There's no separate
+/// "is every page in range actually mapped" walk to do beyond that: this
+/// kernel keeps each task's regions as a handful of whole `[start, end)`
+/// intervals rather than a page table, so one bounds check against those
+/// intervals already covers every page in between.
+pub fn validate_user_range(ptr: usize, len: usize, want_write: bool) -> bool {
+    crate::task::user_range_permitted(ptr, len, want_write)
+}
+
+/// validate then write `val` to a user pointer, in place of dereferencing it
+/// and trusting a hardware fault to catch a bad one — which this kernel's
+/// flat, identity-mapped memory would never actually raise, since a bad
+/// pointer is still ordinary, physically writable RAM as far as the
+/// hardware is concerned. Returns `None`, instead of panicking or
+/// corrupting unrelated memory, if any byte of `[ptr, ptr +
+/// size_of::<T>())` isn't a writable part of the calling task's own address
+/// space.
+pub fn copy_to_user<T: Copy>(ptr: *mut T, val: T) -> Option<()> {
+    if ptr.is_null() || !validate_user_range(ptr as usize, core::mem::size_of::<T>(), true) {
+        return None;
+    }
+    unsafe {
+        translated_write(ptr, val);
+    }
+    Some(())
+}
+
+/// validate then read a `T` out of a user pointer; see [`copy_to_user`] for
+/// why this exists instead of dereferencing `ptr` directly. Returns `None`
+/// if any byte of `[ptr, ptr + size_of::<T>())` isn't a readable part of the
+/// calling task's own address space.
+pub fn copy_from_user<T: Copy>(ptr: *const T) -> Option<T> {
+    if ptr.is_null() || !validate_user_range(ptr as usize, core::mem::size_of::<T>(), false) {
+        return None;
+    }
+    Some(unsafe { translated_read(ptr) })
+}
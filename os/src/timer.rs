@@ -0,0 +1,21 @@
+//! RISC-V timer-related functionality
+
+use crate::config::{CLOCK_FREQ, TICKS_PER_SEC};
+use crate::sbi::set_timer;
+use riscv::register::time;
+
+/// read the `mtime` register
+pub fn get_time() -> usize {
+    time::read()
+}
+
+/// get the current time in milliseconds
+pub fn get_time_ms() -> usize {
+    get_time() / (CLOCK_FREQ / 1000)
+}
+
+/// set the next timer interrupt, one time slice (`CLOCK_FREQ / TICKS_PER_SEC`
+/// ticks) from now
+pub fn set_next_trigger() {
+    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+}
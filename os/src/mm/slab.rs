@@ -0,0 +1,124 @@
+//! A slab allocator for fixed-size objects, layered over [`super::buddy`]
+//!
+//! Each [`SlabCache<T>`] carves pages pulled from [`crate::mm::alloc_contiguous`]
+//! into same-sized slots for `T` and threads the free ones into an
+//! intrusive list, the same `write_next`/`read_next`-through-the-object
+//! trick [`super::buddy`] already uses for its own free lists — there's no
+//! heap allocator anywhere in this kernel to layer a slab cache over
+//! instead (see this module's note on [`SlabCache::alloc`] for why that
+//! matters for `T`'s minimum size).
+//!
+//! Nothing in this kernel actually calls into a `SlabCache` yet:
+//! [`crate::task::TaskControlBlock`] and trap contexts, the two examples
+//! this was requested for, are slots in fixed-size arrays sized by
+//! `MAX_APP_NUM`, not individually allocated and freed — see
+//! [`crate::task`]'s own lazy_static block. This is infrastructure for
+//! whenever that changes, not a drop-in speedup for code that doesn't
+//! allocate today.
+
+use super::{alloc_contiguous, PAGE_SIZE};
+use crate::sync::SpinLock;
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+/// the most pages a single [`SlabCache`] will claim from the frame
+/// allocator before `alloc` starts returning `None`; a handful is enough
+/// for any fixed-size object this kernel currently has in mind, and keeps
+/// the cache's own bookkeeping a fixed-size array like everything else here
+const MAX_SLAB_PAGES: usize = 4;
+
+const NONE: usize = usize::MAX;
+
+struct SlabState {
+    /// head of the intrusive free-slot list, chained through each free
+    /// slot's own first word, or [`NONE`] if empty
+    free_head: usize,
+    /// how many pages this cache has claimed from [`alloc_contiguous`] so
+    /// far, capped at [`MAX_SLAB_PAGES`]; nothing ever hands a claimed page
+    /// back (see [`SlabCache`]'s doc comment), so this only ever grows
+    page_count: usize,
+}
+
+/// a typed fixed-size-object allocator; see this module's own doc comment
+pub struct SlabCache<T> {
+    inner: SpinLock<SlabState>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> SlabCache<T> {
+    /// an empty cache that claims its first page lazily, on the first
+    /// [`alloc`](Self::alloc) call
+    pub const fn new() -> Self {
+        Self {
+            inner: SpinLock::new(SlabState {
+                free_head: NONE,
+                page_count: 0,
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// bytes set aside per object; at least `size_of::<T>()`, but never
+    /// less than a pointer's width, since a free slot's own bytes double as
+    /// the intrusive free-list link until it's allocated
+    fn slot_size() -> usize {
+        size_of::<T>().max(size_of::<usize>())
+    }
+
+    fn grow(&self, state: &mut SlabState) -> bool {
+        if state.page_count >= MAX_SLAB_PAGES {
+            return false;
+        }
+        let Some(ppn) = alloc_contiguous(0) else {
+            return false;
+        };
+        state.page_count += 1;
+        let base = ppn.addr();
+        let slot_size = Self::slot_size();
+        let slots = PAGE_SIZE / slot_size;
+        for i in 0..slots {
+            let slot = base + i * slot_size;
+            unsafe {
+                (slot as *mut usize).write_volatile(state.free_head);
+            }
+            state.free_head = slot;
+        }
+        true
+    }
+
+    /// hand out one zeroed `T`-sized, `T`-aligned slot, growing the cache by
+    /// one page from [`alloc_contiguous`] first if every slot already
+    /// claimed is in use; `None` only once [`MAX_SLAB_PAGES`] pages are all
+    /// full, which this kernel has never come close to
+    pub fn alloc(&self) -> Option<*mut T> {
+        let mut state = self.inner.exclusive_access();
+        if state.free_head == NONE && !self.grow(&mut state) {
+            return None;
+        }
+        let slot = state.free_head;
+        state.free_head = unsafe { (slot as *const usize).read_volatile() };
+        unsafe {
+            core::ptr::write_bytes(slot as *mut u8, 0, size_of::<T>());
+        }
+        Some(slot as *mut T)
+    }
+
+    /// return a slot previously handed out by [`alloc`](Self::alloc) to
+    /// this same cache
+    ///
+    /// # Safety
+    /// `ptr` must have come from this cache's own `alloc`, and not already
+    /// have been freed.
+    pub unsafe fn free(&self, ptr: *mut T) {
+        let mut state = self.inner.exclusive_access();
+        let slot = ptr as usize;
+        (slot as *mut usize).write_volatile(state.free_head);
+        state.free_head = slot;
+    }
+}
+
+impl<T> Default for SlabCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
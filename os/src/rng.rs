@@ -0,0 +1,106 @@
+//! A kernel-internal pseudo-random byte source for `sys_getrandom`
+//!
+//! There is no hardware entropy source anywhere in this tree — no
+//! virtio-rng device, no RDRAND-equivalent CSR this target exposes — so
+//! "device entropy" can't mean anything more here than
+//! [`crate::timer::get_cycles`] sampled once at first use. What follows is
+//! an xorshift64* generator seeded from that single sample: good enough to
+//! make two ordinary callers' output differ and to survive a cursory
+//! statistical test, but not a real CSPRNG (it has no reseeding, and its
+//! state is trivially recoverable from a handful of outputs) — callers
+//! wanting cryptographic security have no business trusting this kernel
+//! for it anyway.
+
+use crate::sync::SpinLock;
+use crate::timer::get_cycles;
+use lazy_static::lazy_static;
+
+/// set in `sys_getrandom`'s `flags` to request a fixed, reproducible byte
+/// stream instead of one seeded from the cycle counter — for tests that
+/// need the same "random" bytes on every run
+///
+/// Real `getrandom`'s flags (`GRND_NONBLOCK`, `GRND_RANDOM`, `GRND_INSECURE`)
+/// all live in the low 3 bits; this is a bit of this kernel's own invention
+/// with no Linux equivalent, so it's placed well clear of them instead of
+/// risking a future real flag collision.
+pub const GRND_DETERMINISTIC: u32 = 0x8000_0000;
+
+/// the fixed seed [`GRND_DETERMINISTIC`] mode always starts its generator
+/// from, so the same request returns the same bytes on every call and every
+/// boot
+const DETERMINISTIC_SEED: u64 = 0xD17E_57A7_5EED_0001;
+
+lazy_static! {
+    /// the non-deterministic generator's running state, seeded once from
+    /// [`get_cycles`] the first time anything asks for random bytes without
+    /// [`GRND_DETERMINISTIC`] set
+    static ref RNG_STATE: SpinLock<u64> = SpinLock::new(seed_from_cycles());
+}
+
+fn seed_from_cycles() -> u64 {
+    if crate::config::DETERMINISTIC_MODE {
+        // see `crate::config::DETERMINISTIC_MODE`'s own doc comment: pin
+        // the non-deterministic generator to the same fixed seed
+        // `GRND_DETERMINISTIC` uses for an individual call, so it also
+        // produces the same sequence across boots
+        return DETERMINISTIC_SEED;
+    }
+    // xor-fold a fixed odd constant in so a near-zero cycle count this
+    // early in boot still seeds a nonzero, well-mixed state
+    (get_cycles() as u64 ^ 0x9E37_79B9_7F4A_7C15) | 1
+}
+
+/// advance `state` one xorshift64* step and return the next 8 pseudo-random
+/// bytes' worth of output
+fn next_u64(state: &mut u64) -> u64 {
+    let mut x = *state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    *state = x;
+    x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+fn fill_from(buf: &mut [u8], state: &mut u64) {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let bytes = next_u64(state).to_le_bytes();
+        let n = core::cmp::min(bytes.len(), buf.len() - filled);
+        buf[filled..filled + n].copy_from_slice(&bytes[..n]);
+        filled += n;
+    }
+}
+
+/// fill `buf` with pseudo-random bytes; see [`GRND_DETERMINISTIC`] for the
+/// one recognized flag, any other bit is ignored
+///
+/// A test requesting 32 bytes twice and comparing them would be plain
+/// kernel-internal logic with no dependency on the sibling `user` crate,
+/// calling this function directly — but this crate is built
+/// `#![no_std]`/`#![no_main]` for a bare-metal target with no host test
+/// harness wired up anywhere in this source tree, so there's nothing here
+/// to add such a test to.
+pub fn fill(buf: &mut [u8], flags: u32) {
+    if flags & GRND_DETERMINISTIC != 0 {
+        let mut state = DETERMINISTIC_SEED;
+        fill_from(buf, &mut state);
+    } else {
+        let mut state = RNG_STATE.exclusive_access();
+        fill_from(buf, &mut state);
+    }
+}
+
+/// a fresh pseudo-random `usize`, drawn from the same non-deterministic
+/// generator [`fill`] uses without [`GRND_DETERMINISTIC`] set
+///
+/// Used to seed a task's stack canary (see
+/// [`crate::task::TaskControlBlock::canary`]) — this has the same "not a
+/// real CSPRNG" caveat as the rest of this module, so it's unguessable
+/// only in the sense that matters for a teaching exercise in detecting
+/// stack smashing, not against an adversary who can sample this kernel's
+/// own generator.
+pub fn random_usize() -> usize {
+    let mut buf = [0u8; core::mem::size_of::<usize>()];
+    fill(&mut buf, 0);
+    usize::from_le_bytes(buf)
+}
@@ -0,0 +1,62 @@
+//! Kernel panic-time stack backtrace
+//!
+//! Walks the chain of saved frame pointers on the current task's kernel
+//! stack to print a best-effort call chain when a panic occurs. This relies
+//! on the kernel being compiled with `-C force-frame-pointers=yes` (see
+//! `.cargo/config.toml`) so that every non-leaf function keeps `fp` pointing
+//! at a valid saved-frame record instead of omitting it.
+//!
+//! Each return address is run through [`crate::symtab::resolve_symbol`] to
+//! annotate it with a function name and offset instead of a bare hex
+//! address; see that module's doc comment for why its table is empty (and
+//! every lookup here a miss) in this particular source tree.
+
+use crate::loader::kernel_stack_range;
+use crate::symtab::resolve_symbol;
+use crate::task::TASK_MANAGER;
+use core::arch::asm;
+
+/// print a backtrace of the kernel stack by walking saved frame pointers
+///
+/// A test that deliberately panics and asserts the printed backtrace has
+/// more than one frame would normally be a standalone binary exercised by
+/// running the kernel to completion under a test harness; this repo has no
+/// upstream test suite at any level (kernel or user) to add one to, so this
+/// is exercised only by this doc comment describing the expected shape:
+/// two or more `0x...` lines between `== Kernel Stack Trace ==` and
+/// `== End Stack Trace ==`, one per frame from the panic site up to
+/// wherever the walk hits the `__alltraps` entry and stops.
+///
+/// # Safety
+///
+/// Walks raw memory through the frame pointer chain starting from the
+/// current `fp`. Relies on the kernel having been built with frame pointers
+/// forced on, and stops as soon as `fp` is null or falls outside the
+/// current task's kernel stack range (in particular, this guards against the
+/// walk wandering into the hand-written `__alltraps` assembly entry, which
+/// does not maintain a frame-pointer chain and would otherwise leave `fp`
+/// holding an arbitrary, non-decreasing value forever).
+pub unsafe fn print_stack_trace() {
+    let mut fp: usize;
+    asm!("mv {}, fp", out(reg) fp);
+
+    // each app's kernel stack is only page-aligned, not
+    // `KERNEL_STACK_SIZE`-aligned, so the real bounds have to come from the
+    // loader's own `KernelStack` allocation rather than rounding `fp`/`sp`
+    let (stack_bottom, stack_top) = kernel_stack_range(TASK_MANAGER.get_current_task());
+
+    println!("== Kernel Stack Trace ==");
+    while fp > stack_bottom && fp <= stack_top {
+        let ra = *(fp as *const usize).offset(-1);
+        let saved_fp = *(fp as *const usize).offset(-2);
+        match resolve_symbol(ra) {
+            Some((name, offset)) => println!("0x{:016x}  {}+0x{:x}", ra, name, offset),
+            None => println!("0x{:016x}  <unknown: no symbol table embedded>", ra),
+        }
+        if saved_fp == 0 || saved_fp <= fp {
+            break;
+        }
+        fp = saved_fp;
+    }
+    println!("== End Stack Trace ==");
+}
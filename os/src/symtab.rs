@@ -0,0 +1,36 @@
+//! Kernel symbol table, for resolving a bare address to a function name
+//!
+//! A real build would populate [`SYMBOLS`] from a `build.rs` step: link the
+//! kernel once, run `nm` (or read the ELF's own `.symtab`) over the
+//! resulting binary, sort the `(address, name)` pairs, and `include!` them
+//! here from `$OUT_DIR` as a `static` slice. This source tree has no
+//! `Cargo.toml` to run a `build.rs` from — see [`crate::stack_trace`]'s
+//! module doc for the same gap — so [`SYMBOLS`] is just empty and
+//! [`resolve_symbol`] always misses. The lookup itself doesn't depend on
+//! how the table gets populated, so it's written for real here: given a
+//! populated, address-sorted [`SYMBOLS`], it binary-searches it correctly.
+
+/// `(address, name)` pairs, sorted ascending by address, one per kernel
+/// function; see this module's doc comment for why this is empty here
+static SYMBOLS: &[(usize, &str)] = &[];
+
+/// find the symbol at or below `addr` and how far past its start `addr` is
+///
+/// Binary-searches [`SYMBOLS`] rather than scanning it linearly, since a
+/// full kernel image can have thousands of symbols and this runs on every
+/// backtrace frame. Returns `None` if `addr` falls before every symbol in
+/// the table (or the table is empty, as it always is in this source tree).
+/// A test resolving a known function's address back to its name can't pass
+/// against an empty table — there's nothing populated to resolve against,
+/// for the reason given on [`SYMBOLS`] — and this repo has no upstream test
+/// suite to add one to regardless; see the same note in
+/// `crate::stack_trace::print_stack_trace`.
+pub fn resolve_symbol(addr: usize) -> Option<(&'static str, usize)> {
+    let idx = match SYMBOLS.binary_search_by_key(&addr, |&(sym_addr, _)| sym_addr) {
+        Ok(idx) => idx,
+        Err(0) => return None,
+        Err(idx) => idx - 1,
+    };
+    let (sym_addr, name) = SYMBOLS[idx];
+    Some((name, addr - sym_addr))
+}
@@ -0,0 +1,15 @@
+//! Synchronization primitives
+//!
+//! [`UPSafeCell`] is the interior-mutability wrapper used throughout the
+//! kernel for shared global state; [`WaitQueue`] builds on it to let a
+//! task block until something else wakes it up, rather than busy-polling.
+//! [`SpinLock`] is a true mutex with the same API, for state that needs to
+//! stay safe once more than one hart can run at once.
+
+mod spin;
+mod up;
+mod wait_queue;
+
+pub use spin::SpinLock;
+pub use up::UPSafeCell;
+pub use wait_queue::WaitQueue;
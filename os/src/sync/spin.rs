@@ -0,0 +1,95 @@
+//! A real spinlock, safe to share across harts
+//!
+//! [`UPSafeCell`](super::UPSafeCell) only *asserts* single-hart exclusivity
+//! (and panics on a double borrow); it isn't actually safe if more than one
+//! hart calls `exclusive_access` at once. `SpinLock<T>` enforces mutual
+//! exclusion for real, with an atomic flag, so it's sound under SMP. It
+//! also disables this hart's interrupts for the duration of the critical
+//! section — otherwise a timer interrupt on the hart already holding the
+//! lock could run code that tries to take the same lock again and spin
+//! forever against itself.
+//!
+//! This kernel's boot path only ever brings up hart 0 — there is no
+//! secondary-hart wakeup code anywhere in this tree — so nothing here
+//! actually runs concurrently yet. `SpinLock` is a drop-in replacement for
+//! `UPSafeCell` (same `exclusive_access()` API), used below for the two
+//! globals (`TASK_MANAGER`, `TOTAL_TASKS`) most likely to be touched from
+//! more than one hart once SMP bring-up exists.
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+use riscv::register::sstatus;
+
+/// a mutex that spins instead of blocking, for data shared across harts
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    inner: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    /// wrap `value` in a new, unlocked spinlock
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            inner: UnsafeCell::new(value),
+        }
+    }
+
+    /// spin until the lock is acquired, disabling this hart's interrupts
+    /// first; returns a guard that releases the lock and restores the
+    /// previous interrupt-enable state on drop
+    pub fn exclusive_access(&self) -> SpinLockGuard<'_, T> {
+        let interrupts_were_enabled = sstatus::read().sie();
+        unsafe {
+            sstatus::clear_sie();
+        }
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        SpinLockGuard {
+            lock: self,
+            interrupts_were_enabled,
+        }
+    }
+}
+
+/// RAII guard returned by [`SpinLock::exclusive_access`]
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+    interrupts_were_enabled: bool,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.inner.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.inner.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    /// release the lock, then re-enable interrupts, but only if they were
+    /// enabled before `exclusive_access` was called — nesting two
+    /// `SpinLock`s must not re-enable interrupts while the outer one is
+    /// still held
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+        if self.interrupts_were_enabled {
+            unsafe {
+                sstatus::set_sie();
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! A `RefCell` wrapper that is `Sync`
+//!
+//! This kernel only ever runs on a single hart at a time, so sharing a
+//! `RefCell` across `static`s (which must be `Sync`) is actually safe, as
+//! long as nothing tries to hold two borrows at once. `UPSafeCell` just
+//! asserts that on our behalf.
+
+use core::cell::{RefCell, RefMut};
+
+/// wraps a value in a `RefCell` and unsafely asserts it is `Sync`
+pub struct UPSafeCell<T> {
+    inner: RefCell<T>,
+}
+
+unsafe impl<T> Sync for UPSafeCell<T> {}
+
+impl<T> UPSafeCell<T> {
+    /// create a new `UPSafeCell`
+    ///
+    /// # Safety
+    /// the caller must guarantee this kernel never runs on more than one
+    /// hart at once, and that borrows obtained from [`exclusive_access`]
+    /// never overlap
+    ///
+    /// [`exclusive_access`]: UPSafeCell::exclusive_access
+    pub unsafe fn new(value: T) -> Self {
+        Self {
+            inner: RefCell::new(value),
+        }
+    }
+
+    /// get exclusive access to the inner value, panicking if it is already
+    /// borrowed
+    pub fn exclusive_access(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+}
@@ -0,0 +1,91 @@
+//! Negative-`isize` errno constants shared across syscalls, collected in
+//! one place so a given failure mode reads the same way no matter which
+//! syscall returns it, instead of each module picking its own magic
+//! number.
+//!
+//! [`crate::mm::EFAULT`] is the one exception: it stays defined in
+//! `crate::mm` instead, since that module's own pointer-validation helpers
+//! need to return it directly without depending back on `crate::syscall`.
+
+/// returned by [`super::dispatch_syscall`] when `syscall_id` doesn't match
+/// any syscall this kernel implements, the same value real Linux's
+/// `ENOSYS` uses
+pub const ENOSYS: isize = -38;
+
+/// the value `sys_mutex_lock`/`sys_semaphore_down` return when deadlock
+/// detection refuses to let the caller wait for the resource
+pub const DEADLOCK_ERRNO: isize = -0xDEAD;
+
+/// the value `sys_fork`/`sys_spawn`/`sys_thread_create`/`sys_clone` return
+/// when every [`crate::config::MAX_APP_NUM`] task slot is occupied, the
+/// same way real `fork`/`clone` return `EAGAIN` when a resource limit is
+/// hit rather than panicking or writing past a fixed table
+///
+/// This kernel has no `alloc` crate linked anywhere, so the task table is a
+/// fixed-size array rather than something a `Vec` could grow without
+/// bound; `MAX_APP_NUM` concurrently-live tasks is accordingly a hard
+/// ceiling here, not a default that can be raised by switching containers.
+pub const EAGAIN: isize = -11;
+
+/// returned by `sys_getcwd` when the caller's buffer is too small to hold
+/// the working directory plus its nul terminator, the same value real
+/// Linux's `ERANGE` uses
+pub const ERANGE: isize = -34;
+
+/// returned by `sys_mkdir` when `path` already names a directory, the same
+/// value real Linux's `EEXIST` uses
+pub const EEXIST: isize = -17;
+
+/// returned by `sys_prctl` when `op` doesn't match a supported `PR_*`
+/// option, the same value real Linux's `EINVAL` uses
+pub const EINVAL: isize = -22;
+
+/// returned by `sys_ftruncate` when `len` would grow a file past
+/// [`crate::config::MAX_FILE_SIZE`], the same value real Linux's `EFBIG`
+/// uses
+pub const EFBIG: isize = -27;
+
+/// returned by `sys_open`/`sys_pipe`/`sys_dup` when the caller's
+/// `RLIMIT_NOFILE` (see `sys_setrlimit`) is already exhausted, the same
+/// value real Linux's `EMFILE` uses
+pub const EMFILE: isize = -24;
+
+/// returned by `sys_mmap`/`sys_mmap_file` when the caller's `RLIMIT_AS`
+/// (see `sys_setrlimit`) would be exceeded, the same value real Linux's
+/// `ENOMEM` uses
+pub const ENOMEM: isize = -12;
+
+/// returned by `sys_lseek` when `fd` names a pipe, the console, or anything
+/// else with no file offset to seek, the same value real Linux's `ESPIPE`
+/// uses
+pub const ESPIPE: isize = -29;
+
+/// returned by `sys_pause` once its blocking wait ends and its caller's
+/// installed handler (if any) has run, the same value real Linux's
+/// `EINTR` uses; a signal with no handler installed terminates the task
+/// instead (see `crate::task::TaskManager::handle_pending_signal_current`),
+/// so `sys_pause` never actually returns in that case
+pub const EINTR: isize = -4;
+
+/// returned by `sys_stat` when `path` doesn't name an existing file, the
+/// same value real Linux's `ENOENT` uses
+pub const ENOENT: isize = -2;
+
+/// returned by `sys_write`/`sys_read` when `fd` is closed, was never
+/// opened, or (for `sys_write`) names something that can only ever be read
+/// from (stdin, a pipe's read end, the flat directory, a `/proc` stat
+/// file), the same value real Linux's `EBADF` uses
+pub const EBADF: isize = -9;
+
+/// returned by `sys_openat` when `dirfd` is a valid fd but doesn't name an
+/// open directory, the same value real Linux's `ENOTDIR` uses
+pub const ENOTDIR: isize = -20;
+
+/// returned by `sys_prlimit` when `pid` doesn't name any live task, the
+/// same value real Linux's `ESRCH` uses
+pub const ESRCH: isize = -3;
+
+/// returned by `sys_prlimit` when the caller tries to raise a target
+/// task's hard limit without being [`crate::task::INITPROC_PID`], the same
+/// value real Linux's `EPERM` uses
+pub const EPERM: isize = -1;
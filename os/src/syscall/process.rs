@@ -0,0 +1,2193 @@
+//! Process-related syscalls
+
+use super::errno::{DEADLOCK_ERRNO, EAGAIN, EINTR, EINVAL, ENOMEM, EPERM, ESRCH};
+use super::TOTAL_TASKS;
+use crate::config::{
+    APP_SIZE_LIMIT, MAX_ARG_LEN, MAX_EXEC_ARGS, MAX_HARTS, MAX_SYSCALL_NUM, MAX_TASK_NAME_LEN,
+    VERBOSE_EXIT_STATS,
+};
+use crate::mm::{
+    copy_to_user, translated_read, translated_write, validate_user_range, HeapStats, EFAULT,
+    PAGE_SIZE,
+};
+use crate::task::{
+    canary_current, change_current_brk, children_of_current, clone_current, condvar_create_current,
+    condvar_signal_current, condvar_wait_current, current_child_rusage, current_child_times_ms,
+    enable_deadlock_detect_current, exec_current, exit_current_and_run_next, fork_current,
+    futex_wait_current, futex_wake_current, get_affinity, get_current_pid, get_current_ppid, get_current_task,
+    madvise_current, memory_map_current, mmap_current, mmap_file_current, mprotect_current, mutex_create_current, mutex_lock_current,
+    ALL_HARTS_MASK, INITPROC_PID, MADV_DONTNEED, MADV_WILLNEED, MAP_POPULATE,
+    mutex_unlock_current, munmap_current, name_current, rlimit_as_current, rlimit_cpu_current, rlimit_nofile_current,
+    rlimit_nproc_current, rlimits_of, semaphore_create_current,
+    semaphore_down_current, semaphore_up_current, set_affinity, set_current_priority, set_name_current,
+    set_rlimit_as_current, set_rlimit_as_of, set_rlimit_cpu_current, set_rlimit_cpu_of,
+    set_rlimit_nofile_current, set_rlimit_nofile_of, set_rlimit_nproc_current, set_rlimit_nproc_of,
+    shmat_current, shmget_current, spawn_current,
+    cpu_util_pct, runqueue_stats, suspend_current_and_run_next, thread_create_current,
+    total_procs, waitpid_blocking_current, waitpid_current, waittid_current, yield_to_current,
+    AcquireOutcome, MapKind, SwitchCause, TaskStatus, WaitResult,
+};
+use crate::rng::fill as rng_fill;
+use crate::timer::{get_cycles, get_time_ms, get_time_ms_for, sleep_until, CLOCK_PROCESS_CPUTIME_ID};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Time value returned by `sys_get_time`
+pub struct TimeVal {
+    /// seconds
+    pub sec: usize,
+    /// microseconds
+    pub usec: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Process times returned by `sys_times`, in milliseconds rather than the
+/// real `times(2)`'s clock ticks — this kernel has no clock-tick unit
+/// distinct from the millisecond timer it already tracks task time with
+/// (see [`crate::syscall::TaskStatBlock`]), so it reuses that unit here too
+pub struct Tms {
+    /// user-mode time accumulated by the calling task itself
+    pub utime: usize,
+    /// kernel-mode time accumulated by the calling task itself
+    pub stime: usize,
+    /// user-mode time accumulated by all of the calling task's reaped
+    /// children (transitively including children *they* had reaped)
+    pub cutime: usize,
+    /// the kernel-mode counterpart of [`cutime`](Self::cutime)
+    pub cstime: usize,
+}
+
+/// `sys_getrusage`'s `who`: the calling task itself
+pub const RUSAGE_SELF: i32 = 0;
+/// `sys_getrusage`'s `who`: the calling task's reaped children, combined
+pub const RUSAGE_CHILDREN: i32 = -1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Resource usage returned by `sys_getrusage`; only the fields this kernel
+/// can meaningfully report are included, unlike the dozen-plus fields (most
+/// permanently zero on Linux too) in the real `struct rusage`
+pub struct Rusage {
+    /// time spent executing user-mode code, in milliseconds rather than the
+    /// real `struct timeval` — same reasoning as [`Tms`]
+    pub ru_utime_ms: usize,
+    /// time spent executing kernel code on the task's behalf, in
+    /// milliseconds
+    pub ru_stime_ms: usize,
+    /// peak resident memory footprint, in KB; see [`sys_getrusage`] for why
+    /// this is each app's fixed flat-memory slot size rather than an actual
+    /// observed peak
+    pub ru_maxrss: usize,
+    /// number of times the task gave up the CPU of its own accord
+    pub ru_nvcsw: usize,
+    /// number of times the task was preempted by the timer interrupt
+    pub ru_nivcsw: usize,
+}
+
+/// resource usage for `who` (`RUSAGE_SELF` or `RUSAGE_CHILDREN`), written
+/// through `usage`; returns `-1` (`EINVAL`-equivalent) for any other `who`,
+/// or `EFAULT` if `usage` can't be written to
+///
+/// This kernel's flat-memory apps live in a fixed-size slot
+/// ([`APP_SIZE_LIMIT`]) for their whole lifetime rather than growing a
+/// resident set that could actually peak at some point below it, so
+/// `ru_maxrss` is always exactly that slot's size — for `RUSAGE_CHILDREN`,
+/// the largest such size among every child reaped so far (matching real
+/// `getrusage`'s "largest, not summed" semantics for `ru_maxrss`, unlike
+/// `ru_nvcsw`/`ru_nivcsw`/[`Tms::cutime`]/[`Tms::cstime`], which are all
+/// sums).
+///
+/// A test spawning a CPU-heavy child, waiting on it, and asserting a
+/// `RUSAGE_CHILDREN` field came back nonzero has the same problem as the
+/// one on [`sys_times`]: no `user` crate in this snapshot to build such a
+/// child against, and no upstream test suite to add one to regardless.
+pub fn sys_getrusage(who: i32, usage: *mut Rusage) -> isize {
+    let rusage = match who {
+        RUSAGE_SELF => {
+            let (utime, stime) = TOTAL_TASKS.get_slot_times_ms(get_current_task());
+            let (nvcsw, nivcsw) = TOTAL_TASKS.get_slot_switches(get_current_task());
+            Rusage {
+                ru_utime_ms: utime,
+                ru_stime_ms: stime,
+                ru_maxrss: APP_SIZE_LIMIT / 1024,
+                ru_nvcsw: nvcsw,
+                ru_nivcsw: nivcsw,
+            }
+        }
+        RUSAGE_CHILDREN => {
+            let (cutime, cstime) = current_child_times_ms();
+            let (cnvcsw, cnivcsw, cmaxrss) = current_child_rusage();
+            Rusage {
+                ru_utime_ms: cutime,
+                ru_stime_ms: cstime,
+                ru_maxrss: cmaxrss,
+                ru_nvcsw: cnvcsw,
+                ru_nivcsw: cnivcsw,
+            }
+        }
+        _ => return -1,
+    };
+    match copy_to_user(usage, rusage) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// System-wide information returned by `sys_sysinfo`
+///
+/// The real `struct sysinfo` also has `totalswap`/`freeswap`/`totalhigh`/
+/// `freehigh`/`sharedram`/`bufferram` fields; this kernel has no swap, no
+/// high memory, and no page cache to report any of those for, so they're
+/// omitted rather than filled in with fabricated numbers. `loads` (the
+/// classic 1/5/15-minute load averages) is approximated below by
+/// `cpu_util_pct`/`util_pct` instead: a true load average needs a
+/// run-queue-length integral sampled over minutes, which this kernel
+/// doesn't keep, but per-hart idle-vs-busy cycle accounting (see
+/// [`crate::task::TaskManager::note_cycles`]) is already enough for a
+/// meaningful 1-second-smoothed utilization percentage.
+pub struct SysInfo {
+    /// seconds since boot
+    pub uptime_sec: usize,
+    /// total pages in [`crate::mm`]'s buddy-system frame allocator's arena
+    ///
+    /// This is a small, separately reserved arena (see
+    /// [`crate::mm::BuddyAllocator`]'s own doc comment), not a count over
+    /// all of physical RAM — every app still lives in its own fixed,
+    /// statically-sized flat-memory slot (see [`crate::config::APP_SIZE_LIMIT`])
+    /// outside this arena entirely, and `sys_mmap` writes directly to
+    /// whatever physical address the caller names rather than drawing from
+    /// it (see [`crate::task::TaskManager::mmap_current`]).
+    pub total_frames: usize,
+    /// the free-frame counterpart of [`total_frames`](Self::total_frames)
+    pub free_frames: usize,
+    /// total kernel heap size, in bytes; see [`crate::mm::HeapStats`]
+    pub total_heap: usize,
+    /// the free-heap counterpart of [`total_heap`](Self::total_heap)
+    pub free_heap: usize,
+    /// number of task slots currently in use (`UnInit` slots excluded)
+    pub procs: usize,
+    /// each hart's CPU utilization percentage (0-100), smoothed over the
+    /// last full 1-second window; see
+    /// [`crate::task::TaskManager::cpu_util_pct`]
+    pub cpu_util_pct: [usize; MAX_HARTS],
+    /// the simple mean of `cpu_util_pct` across every hart
+    pub util_pct: usize,
+}
+
+/// system-wide uptime, memory, and process-count snapshot, written through
+/// `info`; see [`SysInfo`] for which of the real `sysinfo(2)` fields this
+/// kernel can and can't meaningfully report
+///
+/// `uptime_sec` and `procs` are read from [`crate::timer::get_time_ms`] and
+/// [`crate::task::TaskManager::total_procs`] respectively without holding a
+/// single lock across both, so in principle a task could exit between the
+/// two reads; unlike the memory figures, which the request asks to be
+/// computed atomically with respect to the allocator lock (and which are
+/// always `0` here regardless, since there is no allocator lock to speak
+/// of), `procs` racing an exit by a millisecond isn't a self-consistency
+/// problem worth a shared lock over.
+///
+/// A test that allocates via `sys_mmap` and observes `free_frames` dropping
+/// would still miss the mark: `sys_mmap` never draws from
+/// [`crate::mm::BuddyAllocator`]'s arena, so its `free_frames` doesn't move.
+/// See [`SysInfo::total_frames`].
+///
+/// A test spinning one hart on a CPU-bound task while another sits idle
+/// and comparing their two `cpu_util_pct` entries would be a binary in the
+/// sibling `user` crate this kernel loads at boot (and would need a way to
+/// pin a task to a specific hart, which this kernel doesn't expose to
+/// userspace at all); that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to.
+pub fn sys_sysinfo(info: *mut SysInfo) -> isize {
+    let (total_frames, free_frames) = crate::mm::frame_counts();
+    let (total_heap, allocated_heap, _peak, _live) = HeapStats::snapshot();
+    let cpu_util_pct = cpu_util_pct();
+    let util_pct = cpu_util_pct.iter().sum::<usize>() / MAX_HARTS;
+    let sysinfo = SysInfo {
+        uptime_sec: get_time_ms() / 1000,
+        total_frames,
+        free_frames,
+        total_heap,
+        free_heap: total_heap - allocated_heap,
+        procs: total_procs(),
+        cpu_util_pct,
+        util_pct,
+    };
+    match copy_to_user(info, sysinfo) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// kernel heap statistics returned by `sys_heapinfo`; see
+/// [`crate::mm::HeapStats`]
+pub struct HeapInfo {
+    /// total size of the kernel heap arena, in bytes
+    pub total_bytes: usize,
+    /// bytes currently allocated and not yet freed
+    pub allocated_bytes: usize,
+    /// the largest `allocated_bytes` has ever been
+    pub peak_bytes: usize,
+    /// how many allocations are currently outstanding
+    pub live_count: usize,
+}
+
+/// snapshot the kernel heap's own allocator counters, for leak hunting; see
+/// [`HeapInfo`]
+///
+/// A test that boxes a large buffer, checks `allocated_bytes` grows, drops
+/// it, and checks it shrinks back down would need `extern crate alloc` and
+/// a binary in the sibling `user` crate this kernel loads at boot to run
+/// from userspace at all; that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to — see the same note on
+/// `sys_shmat`.
+pub fn sys_heapinfo(info: *mut HeapInfo) -> isize {
+    let (total_bytes, allocated_bytes, peak_bytes, live_count) = HeapStats::snapshot();
+    let heapinfo = HeapInfo {
+        total_bytes,
+        allocated_bytes,
+        peak_bytes,
+        live_count,
+    };
+    match copy_to_user(info, heapinfo) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// the address of the shared vDSO page; see [`crate::timer::vdso_addr`]
+///
+/// A real vDSO needs no syscall at all — it's mapped into every process
+/// automatically at exec time. This kernel has no per-process page table
+/// to map it through (see [`crate::timer`]'s vDSO doc), so the one thing
+/// still missing is a way for userspace to learn the shared page's
+/// address in the first place; this is that.
+pub fn sys_vdso_addr() -> isize {
+    crate::timer::vdso_addr() as isize
+}
+
+/// length of each [`Utsname`] field, matching the real `struct utsname`
+pub const UTSNAME_LENGTH: usize = 65;
+
+/// pack `s` into a null-terminated, null-padded `UTSNAME_LENGTH`-byte field,
+/// truncating if it doesn't fit
+fn utsname_field(s: &str) -> [u8; UTSNAME_LENGTH] {
+    let mut field = [0u8; UTSNAME_LENGTH];
+    let bytes = s.as_bytes();
+    let len = bytes.len().min(UTSNAME_LENGTH - 1);
+    field[..len].copy_from_slice(&bytes[..len]);
+    field
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// System identity returned by `sys_uname`
+pub struct Utsname {
+    /// operating system name
+    pub sysname: [u8; UTSNAME_LENGTH],
+    /// network node hostname
+    pub nodename: [u8; UTSNAME_LENGTH],
+    /// operating system release
+    pub release: [u8; UTSNAME_LENGTH],
+    /// operating system version
+    pub version: [u8; UTSNAME_LENGTH],
+    /// hardware architecture
+    pub machine: [u8; UTSNAME_LENGTH],
+}
+
+/// system identity, written through `buf` as a [`Utsname`]
+///
+/// `release`/`version` would normally be a git commit hash baked in by a
+/// `build.rs` step (`println!("cargo:rustc-env=GIT_HASH=...")` plus
+/// `env!("GIT_HASH")`, or an `include!` of a `build.rs`-generated file); this
+/// source tree has no `Cargo.toml` to run a `build.rs` from at all (the same
+/// gap noted in `crate::symtab`'s module doc), so they're plain string
+/// literals here instead — still real values, just not build-derived ones.
+///
+/// A test reading this back and comparing `sysname` against the kernel's own
+/// name can't be added here: this repo has no upstream test suite at any
+/// level to add one to, the same gap noted throughout `crate::syscall`.
+pub fn sys_uname(buf: *mut Utsname) -> isize {
+    let utsname = Utsname {
+        sysname: utsname_field("rCore"),
+        nodename: utsname_field("rcore"),
+        release: utsname_field("0.1.0"),
+        version: utsname_field("unknown"),
+        machine: utsname_field("riscv64"),
+    };
+    match copy_to_user(buf, utsname) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// Task information returned by `sys_task_info`
+pub struct TaskInfo {
+    /// Status of the task: Running, Ready, Blocked, or Exited (a zombie
+    /// pending `sys_waitpid`) — see [`TaskStatus`]. Doubles as this
+    /// syscall's state enum; there's no separate one, since `TaskStatus`
+    /// already distinguishes exactly those cases.
+    pub status: TaskStatus,
+    /// the task's scheduling priority, set via `sys_set_priority`; see
+    /// [`crate::task::TaskControlBlock::priority`]
+    pub priority: isize,
+    /// the task's accumulated stride under stride scheduling — how far
+    /// along its own pass sequence it's advanced so far; see
+    /// [`crate::task::TaskControlBlock::stride`]
+    pub stride: usize,
+    /// The number of times each syscall has been called by the task
+    pub syscall_times: [u32; MAX_SYSCALL_NUM],
+    /// Total CPU microseconds spent inside each syscall, measured from
+    /// entry to return in `syscall()`. Time the task spent blocked partway
+    /// through a syscall (e.g. `sys_read` waiting on stdin) is excluded —
+    /// only time the task was actually running counts; see
+    /// `crate::syscall::TaskStatBlock::credit_syscall_time`.
+    pub syscall_time_us: [u64; MAX_SYSCALL_NUM],
+    /// Total running time of the task in milliseconds, counted from its
+    /// first dispatch
+    pub time: usize,
+    /// Total microseconds spent on trap entry/exit overhead: every trap's
+    /// wall-clock span minus whatever syscall body time (already broken
+    /// out in `syscall_time_us`) fell inside it. Lets profiling separate
+    /// `syscall()`'s own bookkeeping and dispatch cost from the syscalls
+    /// it dispatches; see `crate::syscall::TotalTasks::record_trap_overhead`.
+    pub trap_overhead_us: u64,
+    /// Peak user stack usage in bytes so far: the reserved stack's top
+    /// minus the lowest `sp` ever observed for this task. Sampled on every
+    /// trap rather than on a stack-growth fault — this kernel's user
+    /// stacks are eagerly backed by ordinary memory from the start (see
+    /// `crate::task::TaskControlBlock::stack_low_water_sp`), so there's no
+    /// fault to watch.
+    pub peak_stack_bytes: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// One task's `ps`-style summary row, returned by `sys_listtasks`
+pub struct TaskSummary {
+    /// the task's pid
+    pub pid: usize,
+    /// current scheduling status
+    pub state: TaskStatus,
+    /// current stride-scheduling priority
+    pub priority: isize,
+    /// the task's `sys_prctl`-settable name, nul-padded past `name_len`;
+    /// see [`crate::task::TaskControlBlock::name`]
+    pub name: [u8; MAX_TASK_NAME_LEN],
+    /// how many bytes of `name` are in use
+    pub name_len: usize,
+    /// total time the task has been alive, in milliseconds, from its first
+    /// dispatch
+    pub run_time_ms: usize,
+    /// total number of syscalls the task has made so far, summed across its
+    /// `TaskStatBlock::call_time`
+    pub syscall_count: u32,
+}
+
+/// write up to `cap` [`TaskSummary`] rows, one per live task, into `buf`;
+/// always returns the true number of live tasks, so a caller whose `cap` was
+/// too small knows how big a buffer to retry with, the same convention a
+/// real `getdents`-style syscall would use
+///
+/// A test spawning three apps and asserting the returned list's length and
+/// fields would normally be a binary in the sibling `user` crate this kernel
+/// loads at boot; that crate isn't part of this source tree, so there's
+/// nothing here to add such a binary to.
+pub fn sys_listtasks(buf: *mut TaskSummary, cap: usize) -> isize {
+    let (snapshot, count) = TOTAL_TASKS.list_tasks();
+    let to_write = cap.min(count);
+    let byte_len = to_write * core::mem::size_of::<TaskSummary>();
+    if to_write > 0 && !validate_user_range(buf as usize, byte_len, true) {
+        return EFAULT;
+    }
+    for (i, &(pid, state, priority, name, name_len, run_time_ms, syscall_count)) in
+        snapshot.iter().take(to_write).enumerate()
+    {
+        unsafe {
+            translated_write(
+                buf.add(i),
+                TaskSummary {
+                    pid,
+                    state,
+                    priority,
+                    name,
+                    name_len,
+                    run_time_ms,
+                    syscall_count,
+                },
+            );
+        }
+    }
+    count as isize
+}
+
+/// `sys_prctl`'s `op` value to set the calling task's name from a
+/// nul-terminated (or [`MAX_TASK_NAME_LEN`]-truncated) string at `arg`
+pub const PR_SET_NAME: i32 = 15;
+/// `sys_prctl`'s `op` value to read the calling task's name back into a
+/// caller-supplied buffer at `arg`, at least [`MAX_TASK_NAME_LEN`] bytes
+pub const PR_GET_NAME: i32 = 16;
+
+/// set or read back the calling task's short diagnostic name — `op` is
+/// [`PR_SET_NAME`] or [`PR_GET_NAME`], `arg` a user-space buffer to read the
+/// new name from or write the current one into
+///
+/// Real `prctl` supports dozens of unrelated `PR_*` operations; only the two
+/// naming ones are implemented here, the rest fall through to [`EINVAL`] the
+/// same way an unsupported syscall id falls through to `ENOSYS` in
+/// [`super::dispatch_syscall`]. A test setting a name and reading it back
+/// via `sys_listtasks` would be a binary in the sibling `user` crate this
+/// kernel loads at boot; that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to.
+pub fn sys_prctl(op: i32, arg: *mut u8) -> isize {
+    match op {
+        PR_SET_NAME => {
+            if !validate_user_range(arg as usize, MAX_TASK_NAME_LEN, false) {
+                return EFAULT;
+            }
+            let mut buf = [0u8; MAX_TASK_NAME_LEN];
+            let mut len = 0;
+            unsafe {
+                while len < MAX_TASK_NAME_LEN {
+                    let byte = *arg.add(len);
+                    if byte == 0 {
+                        break;
+                    }
+                    buf[len] = byte;
+                    len += 1;
+                }
+            }
+            set_name_current(&buf[..len]);
+            0
+        }
+        PR_GET_NAME => {
+            if !validate_user_range(arg as usize, MAX_TASK_NAME_LEN, true) {
+                return EFAULT;
+            }
+            let (name, name_len) = name_current();
+            for (i, &byte) in name[..name_len].iter().enumerate() {
+                unsafe {
+                    translated_write(arg.add(i), byte);
+                }
+            }
+            if name_len < MAX_TASK_NAME_LEN {
+                unsafe {
+                    translated_write(arg.add(name_len), 0u8);
+                }
+            }
+            0
+        }
+        _ => EINVAL,
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// One address-space region, returned by `sys_maps`
+pub struct MapEntry {
+    /// first virtual page number in the region
+    pub start_vpn: usize,
+    /// one past the last virtual page number in the region
+    pub end_vpn: usize,
+    /// `port`-style permission bits (bit 0 = readable, bit 1 = writable,
+    /// bit 2 = executable); exact for a [`MapKind::Mmap`] region, but
+    /// nominal (not independently enforced) for every other kind, since
+    /// this kernel has no per-page permissions outside the `mmap`/W^X
+    /// machinery `mmap_current`/`mprotect_current` apply
+    pub perm: usize,
+    /// what the region is used for
+    pub kind: MapKind,
+}
+
+/// write up to `cap` [`MapEntry`] rows, one per region in the current
+/// task's address space, into `buf`; always returns the true number of
+/// regions, the same too-small-`cap` convention as `sys_listtasks`
+///
+/// A test that `mmap`s a region and then finds a matching entry in the
+/// returned list would normally be a binary in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree,
+/// so there's nothing here to add such a binary to.
+pub fn sys_maps(buf: *mut MapEntry, cap: usize) -> isize {
+    let (snapshot, count) = memory_map_current();
+    let to_write = cap.min(count);
+    let byte_len = to_write * core::mem::size_of::<MapEntry>();
+    if to_write > 0 && !validate_user_range(buf as usize, byte_len, true) {
+        return EFAULT;
+    }
+    for (i, &(start_vpn, end_vpn, perm, kind)) in snapshot.iter().take(to_write).enumerate() {
+        unsafe {
+            translated_write(
+                buf.add(i),
+                MapEntry {
+                    start_vpn,
+                    end_vpn,
+                    perm,
+                    kind,
+                },
+            );
+        }
+    }
+    count as isize
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// The outcome of a [`sys_pagewalk`] call
+pub struct PageWalkResult {
+    /// whether `vaddr`'s page falls inside any region of the caller's own
+    /// address space
+    pub mapped: bool,
+    /// the physical address `vaddr` resolves to — identical to `vaddr`
+    /// itself, since every task in this kernel runs against one flat,
+    /// identity-mapped physical slot (see [`crate::mm`]'s module doc); `0`
+    /// if `mapped` is `false`
+    pub phys_addr: usize,
+    /// the containing region's [`MapEntry::perm`]-style permission bits;
+    /// `0` if `mapped` is `false`
+    pub perm: usize,
+    /// the containing region's kind; meaningless if `mapped` is `false`
+    pub kind: MapKind,
+}
+
+/// walk the caller's own address space for `vaddr`, reporting the region
+/// it falls in (if any) and the physical address it resolves to; returns
+/// `-1` if [`crate::config::PAGEWALK_DEBUG`] is off
+///
+/// The request this answers asked for the PTE at each level of a real
+/// multi-level page-table walk, the way a kernel with per-task page tables
+/// would report one level per call to `satp`'s root table and then each
+/// subsequent PTE's own pointer. This kernel has no page tables at all —
+/// every task runs against one flat, identity-mapped physical slot, and
+/// `satp` is never written (see [`crate::mm`]'s module doc) — so there is
+/// only ever one "level" to report: whether `vaddr`'s page falls inside
+/// one of the caller's own address-space regions, the same regions
+/// [`sys_maps`] enumerates. This reuses [`memory_map_current`] read-only,
+/// same as `sys_maps` does, rather than duplicating its region logic.
+///
+/// A test walking a mapped address (e.g. the caller's own code, at its
+/// program counter) and an unmapped one (e.g. address `0`, always below
+/// every app's loaded image; see `crate::loader`) and checking `mapped`
+/// differs between the two would be a binary in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree,
+/// so there's nothing here to add such a binary to.
+pub fn sys_pagewalk(vaddr: usize, out: *mut PageWalkResult) -> isize {
+    if !crate::config::PAGEWALK_DEBUG {
+        return -1;
+    }
+    let vpn = vaddr / PAGE_SIZE;
+    let (snapshot, count) = memory_map_current();
+    let region = snapshot[..count]
+        .iter()
+        .find(|&&(start_vpn, end_vpn, _, _)| vpn >= start_vpn && vpn < end_vpn);
+    let result = match region {
+        Some(&(_, _, perm, kind)) => PageWalkResult {
+            mapped: true,
+            phys_addr: vaddr,
+            perm,
+            kind,
+        },
+        None => PageWalkResult {
+            mapped: false,
+            phys_addr: 0,
+            perm: 0,
+            kind: MapKind::CodeData,
+        },
+    };
+    match copy_to_user(out, result) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// task exits and submit an exit code
+///
+/// If [`VERBOSE_EXIT_STATS`] is set, prints a one-line summary of the
+/// task's total user/kernel time and syscall count first — while its
+/// [`crate::syscall::TaskStatBlock`] is still the current task's own,
+/// before [`exit_current_and_run_next`] hands its slot off to the
+/// scheduler.
+///
+/// A test running an app that makes a known number of `sys_write` calls
+/// and asserting the printed syscall count matches would be a binary in
+/// the sibling `user` crate this kernel loads at boot; that crate isn't
+/// part of this source tree, so there's nothing here to add such a binary
+/// to.
+pub fn sys_exit(exit_code: i32) -> ! {
+    if VERBOSE_EXIT_STATS {
+        let pid = get_current_pid();
+        let (utime, stime) = TOTAL_TASKS.get_slot_times_ms(get_current_task());
+        let syscalls: u32 = TOTAL_TASKS.get_total_syscall_times().iter().sum();
+        println!(
+            "[kernel] pid {} exited: {}ms user, {}ms kernel, {} syscalls",
+            pid, utime, stime, syscalls
+        );
+    }
+    exit_current_and_run_next(exit_code);
+    unreachable!("Unreachable in sys_exit!")
+}
+
+/// shut the whole machine down via the SBI SRST reset, succeeding if
+/// `exit_code == 0` and failing otherwise
+///
+/// Only pid 0 — the first app the loader dispatches at boot, this kernel's
+/// closest equivalent to a privileged initproc, since there is no separate
+/// notion of privilege levels or capabilities anywhere in this flat
+/// multiprogramming kernel — may call this; any other caller is killed
+/// exactly as if it had called [`sys_exit`] with a failure code, rather than
+/// being allowed to bring the machine down out from under its siblings.
+///
+/// There's no dirty buffer cache to flush before resetting: `crate::fs`'s
+/// filesystem lives entirely in RAM already (see `crate::syscall::Stat`'s
+/// doc comment on `dev`), so every write has already landed in its final
+/// resting place the moment the write syscall that made it returns — unlike
+/// a disk-backed filesystem, there's no separate cached copy that could
+/// still be out of sync with storage at shutdown time.
+///
+/// Before resetting, this also checks [`crate::mm::frame_counts`] against
+/// the buddy allocator's own arena size: every page that arena starts with
+/// is free at boot (nothing permanently reserved is ever carved out of it;
+/// see [`crate::mm::buddy`](crate::mm)'s module doc), so by the time the
+/// last task has exited, free should equal total again. A shortfall prints
+/// a leak report — one line per still-allocated page index, via
+/// [`crate::mm::for_each_allocated`] — and forces a failing exit code
+/// regardless of what the caller passed in, the same way an unhandled
+/// signal overrides a task's own requested exit code elsewhere in this
+/// kernel. This can only ever catch a leak from the buddy arena itself,
+/// the one piece of this kernel that behaves like a frame allocator today;
+/// every task's own memory still lives in the fixed, statically reserved
+/// slots `crate::loader` hands out, which were never individually
+/// allocated or freed in the first place, so there's nothing there for a
+/// leak check to even ask about (see [`crate::mm`]'s own module doc on why
+/// a real frame allocator over all of physical RAM doesn't exist yet).
+///
+/// A harness-level integration test that spawns a runner app, has it
+/// deliberately leak a page (call something that allocates from the buddy
+/// arena and never free it), then calls this with a nonzero code and
+/// observes both the leak report and the process's exit status, needs an
+/// actual emulator (QEMU) run to observe the SBI reset's exit behavior from
+/// outside the kernel; this repo has neither a `user` crate to build such a
+/// runner app against nor any harness script to drive QEMU and assert on
+/// its exit code, so there's nothing to add a test to here.
+pub fn sys_shutdown(exit_code: usize) -> ! {
+    if get_current_pid() != 0 {
+        exit_current_and_run_next(-1);
+        unreachable!("Unreachable in sys_shutdown!");
+    }
+    let (total_frames, free_frames) = crate::mm::frame_counts();
+    let leaked = total_frames - free_frames;
+    if leaked != 0 {
+        println!(
+            "[kernel] sys_shutdown: frame leak detected, {} of {} page(s) still allocated:",
+            leaked, total_frames
+        );
+        crate::mm::for_each_allocated(|ppn| {
+            println!("[kernel]   ppn {} still allocated, owning subsystem not tracked", ppn);
+        });
+    }
+    println!(
+        "[kernel] sys_shutdown: uptime {}s, {} task slot(s) in use, exit code {}",
+        get_time_ms() / 1000,
+        total_procs(),
+        exit_code
+    );
+    crate::sbi::shutdown(exit_code != 0 || leaked != 0)
+}
+
+/// current task gives up resources for other tasks
+pub fn sys_yield() -> isize {
+    // a `sys_yield` is by definition a voluntary switch, unlike the timer
+    // interrupt's own call to `suspend_current_and_run_next` in
+    // `crate::trap::trap_handler`; see `sys_getrusage`'s `ru_nvcsw`
+    TOTAL_TASKS.record_voluntary_switch();
+    suspend_current_and_run_next(SwitchCause::Yield);
+    0
+}
+
+/// directly hand the hart off to the task with pid `pid`, if it's runnable
+/// right now, instead of the plain fairness-by-stride pick `sys_yield`
+/// makes — a directed handoff for a latency-sensitive producer/consumer
+/// pair that wants the consumer to run next without waiting behind
+/// unrelated ready work. Returns -1 if `pid` doesn't name a currently
+/// runnable task; see [`crate::task::TaskManager::yield_to_current`] for
+/// the fairness cap that keeps a repeated handoff between the same two
+/// tasks from starving everything else.
+///
+/// A test pairing this against plain `sys_yield` in a ping-pong producer/
+/// consumer and comparing round-trip latency would be a binary in the
+/// sibling `user` crate this kernel loads at boot; that crate isn't part
+/// of this source tree, so there's nothing here to add such a binary to.
+pub fn sys_yield_to(pid: usize) -> isize {
+    TOTAL_TASKS.record_voluntary_switch();
+    if yield_to_current(pid) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// get the current time in milliseconds, for
+/// [`CLOCK_REALTIME`](crate::timer::CLOCK_REALTIME),
+/// [`CLOCK_MONOTONIC`](crate::timer::CLOCK_MONOTONIC), or
+/// [`CLOCK_PROCESS_CPUTIME_ID`](crate::timer::CLOCK_PROCESS_CPUTIME_ID)
+/// (reusing the second, previously-unused argument as `clock_id`; an
+/// unrecognized id is treated as `CLOCK_MONOTONIC`)
+///
+/// See [`crate::timer::get_time_ms_for`] for why `CLOCK_MONOTONIC` is
+/// immune to whatever `CLOCK_REALTIME` reports: nothing in this kernel can
+/// step `CLOCK_REALTIME` in the first place. A test reading both clocks
+/// and asserting the monotonic one never decreases across a sleep would be
+/// a binary in the sibling `user` crate this kernel loads at boot; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such a binary to.
+///
+/// `CLOCK_PROCESS_CPUTIME_ID` reports the calling task's own accumulated
+/// `user_time + kernel_time` — the same two figures `sys_times`'s `utime`/
+/// `stime` report — rather than time since boot, so it advances only while
+/// the task is actually running and stays flat across a `sys_nanosleep` or
+/// any other block. A test that calls this clock, sleeps, calls it again
+/// (expecting no advance), busy-loops, then calls it a third time
+/// (expecting an advance proportional to the busy-loop, not the sleep)
+/// would be a binary in the sibling `user` crate this kernel loads at
+/// boot; that crate isn't part of this source tree, so there's nothing
+/// here to add such a binary to.
+/// `sec`/`usec` can't tear against each other here: both are derived below
+/// from the single `ms` sample taken up front, and the `TimeVal` they're
+/// packed into is written to user memory in one [`copy_to_user`] call
+/// rather than as two separate field writes a preemption could land
+/// between. The shared fast-time page a userspace caller would read
+/// instead of trapping in here already guards its own torn-read window
+/// with a sequence counter — see [`crate::timer::VdsoPage`]'s `seq` field.
+pub fn sys_get_time(ts: *mut TimeVal, clock_id: usize) -> isize {
+    let ms = if clock_id == CLOCK_PROCESS_CPUTIME_ID {
+        let (utime, stime) = TOTAL_TASKS.get_slot_times_ms(get_current_task());
+        utime + stime
+    } else {
+        get_time_ms_for(clock_id)
+    };
+    // `ts` is a user pointer: go through `copy_to_user` rather than
+    // dereferencing it directly, so a `TimeVal` that straddles a page
+    // boundary is still written correctly, and a bad `ts` is rejected with
+    // `EFAULT` instead of corrupting whatever it happened to point at
+    match copy_to_user(
+        ts,
+        TimeVal {
+            sec: ms / 1000,
+            usec: (ms % 1000) * 1000,
+        },
+    ) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// the raw RISC-V `cycle` CSR, for fine-grained microbenchmarks where
+/// [`sys_get_time`]'s millisecond resolution is too coarse; divide a delta
+/// between two calls by [`crate::config::CYCLE_FREQ`] to convert to
+/// seconds (see that constant's doc comment for the caveat on what it
+/// actually measures)
+///
+/// A test spinning a loop of known length and checking the cycle delta
+/// lands in a plausible order of magnitude would be a binary in the
+/// sibling `user` crate this kernel loads at boot; that crate isn't part
+/// of this source tree, so there's nothing here to add such a binary to.
+pub fn sys_getcycles() -> isize {
+    get_cycles() as isize
+}
+
+/// block the calling task until `req` (seconds + microseconds) has
+/// elapsed, instead of it busy-waiting on `sys_get_time`
+///
+/// See [`crate::timer::sleep_until`]; the task is woken by the timer
+/// interrupt once its deadline has passed, not before.
+pub fn sys_nanosleep(req: *const TimeVal) -> isize {
+    if req.is_null() {
+        return -1;
+    }
+    let req = unsafe { translated_read(req) };
+    let deadline_ms = get_time_ms() + req.sec * 1000 + req.usec / 1000;
+    sleep_until(deadline_ms);
+    0
+}
+
+/// `sys_clock_nanosleep`'s `flags`: `req` is an absolute deadline against
+/// `clock_id` rather than a duration to sleep for
+pub const TIMER_ABSTIME: usize = 1;
+
+/// block the calling task until `req` is reached, either as a duration
+/// (`flags` without [`TIMER_ABSTIME`], identical to [`sys_nanosleep`]) or
+/// as an absolute deadline against `clock_id` (with it) — the latter lets
+/// a periodic task compute its next wake time once up front (`deadline +=
+/// period`) and sleep to exactly that instant each cycle, rather than
+/// resleeping for `period` relative to whenever it happened to wake up,
+/// which accumulates drift equal to however late each wake was.
+///
+/// An absolute deadline that's already past (`req <= ` the current
+/// `clock_id` reading) returns immediately rather than blocking, same as
+/// real `clock_nanosleep` — a periodic task running behind schedule
+/// should never oversleep trying to "catch up" to a deadline that's
+/// already gone by.
+///
+/// `clock_id` only matters for interpreting an absolute `req`: the
+/// remaining duration until a [`TIMER_ABSTIME`] deadline is computed in
+/// `clock_id`'s own reading via [`get_time_ms_for`] and then blocked on
+/// exactly that remaining span, so [`CLOCK_REALTIME`](crate::timer::CLOCK_REALTIME)
+/// and [`CLOCK_MONOTONIC`](crate::timer::CLOCK_MONOTONIC) behave
+/// identically here — they already differ by nothing but a constant
+/// offset that's never adjusted (see that module's doc comments), so the
+/// remaining time until a given instant is the same in either clock's
+/// units.
+///
+/// A test running a periodic task that computes `deadline += period` each
+/// cycle and confirms its wake times stay aligned to that fixed period
+/// instead of drifting (the way a plain relative `sys_nanosleep(period)`
+/// loop would) would be a binary in the sibling `user` crate this kernel
+/// loads at boot; that crate isn't part of this source tree, so there's
+/// nothing here to add such a binary to.
+pub fn sys_clock_nanosleep(clock_id: usize, flags: usize, req: *const TimeVal) -> isize {
+    if req.is_null() {
+        return -1;
+    }
+    let req = unsafe { translated_read(req) };
+    let requested_ms = req.sec * 1000 + req.usec / 1000;
+    if flags & TIMER_ABSTIME == 0 {
+        sleep_until(get_time_ms() + requested_ms);
+        return 0;
+    }
+    let now_for_clock = get_time_ms_for(clock_id);
+    if requested_ms <= now_for_clock {
+        return 0;
+    }
+    sleep_until(get_time_ms() + (requested_ms - now_for_clock));
+    0
+}
+
+/// grow (`size > 0`) or shrink (`size < 0`) the current task's heap,
+/// returning its break before the change, or -1 if the change would run
+/// outside the task's reserved memory
+///
+/// Whether growth zeroes the newly claimed bytes is controlled by
+/// [`crate::config::LAZY_HEAP_ZEROING`]; that flag has no effect on
+/// `sys_mmap` below, which always zeroes regardless — a test asserting
+/// that would normally live as a binary in the sibling `user` crate this
+/// kernel loads at boot; that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to.
+pub fn sys_sbrk(size: i32) -> isize {
+    match change_current_brk(size as isize) {
+        Some(old_brk) => old_brk as isize,
+        None => -1,
+    }
+}
+
+/// map `len` bytes of anonymous memory at `start` with the permission bits
+/// in `port` (bit 0 = readable, bit 1 = writable, bit 2 = executable),
+/// optionally OR'd with [`MAP_POPULATE`] to request eager prefaulting
+///
+/// Always zeroes the mapped range eagerly, regardless of
+/// [`crate::config::LAZY_HEAP_ZEROING`] — that flag only ever applies to
+/// `sys_sbrk`'s heap growth, never here. Which also means [`MAP_POPULATE`]
+/// is always this kernel's actual behavior whether or not the caller asks
+/// for it: there's no lazy, fault-driven population to opt out of in the
+/// first place (see [`crate::task::TaskManager::mmap_current`]'s own doc
+/// comment). Passing it is accepted rather than rejected, purely so a
+/// caller that does pass it doesn't see a spurious failure; a test
+/// comparing first-access latency with and without it would find no
+/// difference to measure, since both paths already fault in nothing —
+/// the eager work already happened here, before this call even returns.
+///
+/// There is no megapage opt-in flag here, and there can't be: a 2MiB
+/// megapage PTE is a middle-page-table-level entry that stands in for 512
+/// leaf entries at once, which only means anything on top of real
+/// multi-level page tables. This kernel has none — every mapping here, huge
+/// or not, is just a record in [`crate::task::TaskControlBlock::mmap_areas`]
+/// against a single flat, identity-mapped view of physical memory (see
+/// [`crate::mm`]'s module doc) — so there's no page-table level for a
+/// megapage to collapse, and no TLB pressure it would relieve either.
+pub fn sys_mmap(start: usize, len: usize, port: usize) -> isize {
+    match mmap_current(start, len, port) {
+        Some(()) => 0,
+        // covers both the usual `mmap` mistakes (see `mmap_current`'s doc
+        // comment) and an `RLIMIT_AS` violation; real `mmap` returns
+        // `ENOMEM` for a resource-limit failure too, so there's no need to
+        // distinguish the two here
+        None => ENOMEM,
+    }
+}
+
+/// unmap the `len`-byte region at `start` previously established by
+/// `sys_mmap` or `sys_mmap_file`; `start` must match a mapping's start
+/// exactly
+///
+/// If the unmapped region was a shared file-backed mapping (see
+/// `sys_mmap_file`), its current contents are written back to the backing
+/// file before the mapping is torn down — see
+/// `TaskManager::munmap_current`.
+pub fn sys_munmap(start: usize, len: usize) -> isize {
+    match munmap_current(start, len) {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Arguments for `sys_mmap_file`, bundled behind a pointer since this
+/// kernel's syscall dispatch only forwards three register-sized args (see
+/// [`crate::syscall::syscall`]) and this call needs six
+pub struct MmapFileRequest {
+    /// virtual address to map at, same convention as `sys_mmap`'s `start`
+    pub start: usize,
+    /// length of the mapping in bytes
+    pub len: usize,
+    /// permission bits, same convention as `sys_mmap`'s `port`
+    pub port: usize,
+    /// fd of an already-open regular file to back the mapping with
+    pub fd: usize,
+    /// byte offset into the file at which the mapping begins
+    pub offset: usize,
+    /// 0 for a private (copy-on-write) mapping, nonzero for a shared one;
+    /// only a shared mapping's writes are written back by `sys_munmap` or
+    /// `sys_sync`
+    pub shared: usize,
+}
+
+/// map `req.len` bytes of `req.fd`'s contents, starting at `req.offset`,
+/// into the calling task's address space at `req.start` with the
+/// permission bits in `req.port`
+///
+/// This kernel has no per-task page table (see [`crate::mm`]'s module
+/// doc), and so no real page-fault trap to hang lazy, fault-driven
+/// population off of — the same constraint `sys_mmap`'s anonymous mappings
+/// already live with. So, like `sys_mmap`, the mapped range is populated
+/// eagerly here: the requested file bytes are read in immediately via
+/// [`crate::fs::read_at`] rather than on first access. A private
+/// (`req.shared == 0`) mapping's writes are never written back anywhere; a
+/// shared mapping's writes are written back to `req.fd`'s inode by
+/// `sys_munmap` or `sys_sync` — see `TaskManager::mmap_file_current`.
+///
+/// A test that maps a file, reads through the mapping, writes through a
+/// shared mapping, and confirms the write persists past `sys_munmap` would
+/// normally live as a binary in the sibling `user` crate this kernel loads
+/// at boot; that crate isn't part of this source tree, so there's nothing
+/// here to add such a binary to — see the same note on `sys_mprotect`.
+pub fn sys_mmap_file(req: *const MmapFileRequest) -> isize {
+    if !validate_user_range(req as usize, core::mem::size_of::<MmapFileRequest>(), false) {
+        return EFAULT as isize;
+    }
+    let req = unsafe { translated_read(req) };
+    match mmap_file_current(
+        req.start,
+        req.len,
+        req.port,
+        req.fd,
+        req.offset,
+        req.shared != 0,
+    ) {
+        Some(()) => 0,
+        // same `ENOMEM`-covers-everything convention as `sys_mmap`
+        None => ENOMEM,
+    }
+}
+
+/// change the permission bits of the `len`-byte region at `start`, which
+/// must be entirely covered by prior `sys_mmap` calls with no unmapped gap
+///
+/// Like `sys_mmap`, `port` requesting both writable and executable is
+/// rejected outright, along with any other reserved/empty `port` value —
+/// see `TaskManager::mprotect_current`.
+///
+/// A test mapping RW, writing through it, calling this to switch it to RX,
+/// and confirming a later write faults would normally live as a binary in
+/// the sibling `user` crate this kernel loads at boot; that crate isn't
+/// part of this source tree, so there's nothing here to add such a binary
+/// to — see the same note on `sys_setitimer`.
+pub fn sys_mprotect(start: usize, len: usize, port: usize) -> isize {
+    match mprotect_current(start, len, port) {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+/// give advice about the `len`-byte region at `start`, which must be
+/// entirely covered by prior `sys_mmap`/`sys_mmap_file` calls with no
+/// unmapped gap; `advice` is [`MADV_DONTNEED`] or [`MADV_WILLNEED`]
+///
+/// [`MADV_DONTNEED`] drops the range's backing frames (zeroing it in
+/// place, writing a shared file-backed area's dirty bytes back first) so a
+/// long-running app can release cold memory without fully `sys_munmap`ing
+/// and having to `sys_mmap` it all back later; [`MADV_WILLNEED`] is
+/// accepted but does nothing, since every mapping in this kernel is
+/// already eagerly populated at `mmap` time rather than faulted in lazily
+/// — see `TaskManager::madvise_current`.
+///
+/// A test filling a mapped region, calling this with `MADV_DONTNEED`,
+/// reading back zeros, and confirming the freed frames through
+/// `sys_maps`/a memory-accounting syscall would normally live as a binary
+/// in the sibling `user` crate this kernel loads at boot; that crate isn't
+/// part of this source tree, so there's nothing here to add such a binary
+/// to — see the same note on `sys_mprotect`.
+pub fn sys_madvise(start: usize, len: usize, advice: i32) -> isize {
+    match madvise_current(start, len, advice) {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+/// find or create a System-V-style shared memory segment identified by
+/// `key`, sized for up to `size` bytes, and return its segment id
+///
+/// Two unrelated tasks calling this with the same `key` get back the same
+/// id and, once each calls `sys_shmat` on it, the same physical backing
+/// storage — see [`crate::task::TaskManager::shmget_current`]. `size` is
+/// capped at `SHM_SEGMENT_SIZE`, since this kernel has no frame allocator to
+/// give a segment pages of its own.
+pub fn sys_shmget(key: usize, size: usize) -> isize {
+    match shmget_current(key, size) {
+        Some(id) => id as isize,
+        None => -1,
+    }
+}
+
+/// map shared memory segment `id` (from a prior `sys_shmget`) into the
+/// calling task's address space and return the virtual address it landed at
+///
+/// A test with two processes calling `sys_shmget` on the same key, one
+/// writing through its `sys_shmat` address and the other reading the same
+/// bytes back, would normally live as a binary in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to — see the same note on
+/// `sys_mprotect`.
+pub fn sys_shmat(id: isize) -> isize {
+    if id < 0 {
+        return -1;
+    }
+    match shmat_current(id as usize) {
+        Some(addr) => addr as isize,
+        None => -1,
+    }
+}
+
+/// start a fresh instance of the calling task's own program, without a
+/// `fork` + `exec` pair; `_path` is accepted for ABI compatibility but
+/// ignored, since this kernel has no app-name registry to resolve it against
+/// (see [`crate::task::TaskManager::spawn_current`])
+pub fn sys_spawn(_path: *const u8) -> isize {
+    match spawn_current() {
+        Some(new_pid) => new_pid as isize,
+        None => EAGAIN,
+    }
+}
+
+/// read a single nul-terminated `argv` entry into a fixed-size buffer,
+/// truncating at [`MAX_ARG_LEN`] like [`super::fs::read_path`] does for a
+/// path
+fn read_arg(arg: *const u8) -> ([u8; MAX_ARG_LEN], usize) {
+    let mut buf = [0u8; MAX_ARG_LEN];
+    let mut len = 0;
+    unsafe {
+        while len < MAX_ARG_LEN {
+            let byte = *arg.add(len);
+            if byte == 0 {
+                break;
+            }
+            buf[len] = byte;
+            len += 1;
+        }
+    }
+    (buf, len)
+}
+
+/// replace the calling task's own program image with a fresh instance of
+/// itself, passing it `argv`; `path` is accepted for ABI compatibility but
+/// ignored, since this kernel has no app-name registry to resolve it
+/// against — the same deviation [`sys_spawn`] already makes, for the same
+/// reason (see [`crate::task::TaskManager::exec_current`])
+///
+/// `argv` is a nul-terminated array of nul-terminated C strings, same as a
+/// real `execve`. Returns `-1` if it holds more than [`MAX_EXEC_ARGS`]
+/// entries, the same way a real `execve` fails with `E2BIG`; otherwise
+/// never returns a failure to the caller's own program, since by the time
+/// this returns at all the caller's program image is already gone — it
+/// returns `argc` instead, which becomes the new program's `a0` (see
+/// [`crate::task::TaskManager::exec_current`]'s doc comment for why the
+/// return value, not a write through `cx`, is how that's threaded through).
+///
+/// A test exercising this would need a binary in the sibling `user` crate
+/// this kernel loads at boot that calls `exec` on itself with a marker
+/// argument and checks `argv` came through correctly; that crate isn't
+/// part of this source tree, so there's nothing here to add such a binary
+/// to.
+pub fn sys_exec(_path: *const u8, argv: *const *const u8) -> isize {
+    let mut args = [([0u8; MAX_ARG_LEN], 0usize); MAX_EXEC_ARGS];
+    let mut argc = 0;
+    unsafe {
+        while !(*argv.add(argc)).is_null() {
+            if argc >= MAX_EXEC_ARGS {
+                return -1;
+            }
+            args[argc] = read_arg(*argv.add(argc));
+            argc += 1;
+        }
+    }
+    exec_current(&args, argc) as isize
+}
+
+/// duplicate the calling task; the parent sees the child's pid returned,
+/// the child sees 0 (see [`crate::task::TaskManager::fork_current`])
+pub fn sys_fork() -> isize {
+    match fork_current() {
+        Some(child_pid) => child_pid as isize,
+        None => EAGAIN,
+    }
+}
+
+/// share the address space with the new task, which must be given its own
+/// `stack` to run on (see [`crate::task::TaskManager::clone_current`])
+pub const CLONE_VM: usize = 0x100;
+/// share the fd table with the new task
+///
+/// This kernel stores each task's fd table as a plain per-`TaskControlBlock`
+/// value rather than behind a shared reference, so this bit is accepted but
+/// has no distinguishable effect: the fd table is always copied at clone
+/// time and independently mutable afterwards either way, the same
+/// simplification already documented for [`CLONE_VM`]'s memory sharing.
+pub const CLONE_FILES: usize = 0x400;
+/// share signal dispositions with the new task
+///
+/// Accepted for the same reason as [`CLONE_FILES`]: signal dispositions are
+/// a per-`TaskControlBlock` value here too, always copied rather than
+/// shared.
+pub const CLONE_SIGHAND: usize = 0x800;
+
+/// the generalized `fork`/thread-creation primitive: duplicate the calling
+/// task into a new one that resumes seeing a return value of `0` right
+/// where the caller's own `sys_clone` call returns, running on `stack` if
+/// non-zero (its own stack otherwise) and, if `flags & CLONE_VM` is set,
+/// sharing the caller's address space instead of getting an eagerly
+/// duplicated copy of it
+///
+/// [`sys_fork`] is `sys_clone(0, 0)`; [`sys_thread_create`] is closest to
+/// `sys_clone(CLONE_VM | CLONE_FILES, stack)`, except it starts the new
+/// thread fresh at a caller-chosen entry point rather than resuming from
+/// the clone call itself — both are kept as their own syscalls rather than
+/// rewritten in terms of this one, so existing callers don't have to change.
+///
+/// Returns [`EAGAIN`] if `CLONE_VM` is set with a zero `stack`, or if no
+/// task slot is free — the two cases aren't distinguished, since
+/// [`crate::task::TaskManager::clone_current`] reports both as `None`.
+///
+/// A test covering `sys_clone(0, 0)`, `sys_clone(CLONE_VM, stack)`, and a
+/// mixed `sys_clone(CLONE_VM | CLONE_FILES, stack)` — checking the right
+/// combination of shared memory and independent pid/fd-table state in each
+/// case — would be binaries in the sibling `user` crate this kernel loads at
+/// boot; that crate isn't part of this source tree, so there's nothing here
+/// to add such binaries to.
+pub fn sys_clone(flags: usize, stack: usize) -> isize {
+    match clone_current(flags & CLONE_VM != 0, stack) {
+        Some(child_pid) => child_pid as isize,
+        None => EAGAIN,
+    }
+}
+
+/// wait for a child to exit, reaping it and writing its exit code through
+/// `exit_code_ptr`; `pid == -1` matches any child
+///
+/// Returns the reaped child's pid, `-2` if a matching child exists but
+/// hasn't exited yet, or `-1` if the caller has no such child at all.
+///
+/// Never returns [`EINTR`]: unlike [`sys_wait4`], this never blocks
+/// in-kernel, so there's no in-kernel wait for a signal to interrupt.
+/// `sys_wait4` is there for a caller that wants the blocking behavior.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    match waitpid_current(pid) {
+        WaitResult::Reaped(child_pid, exit_code, _) => {
+            if !exit_code_ptr.is_null() {
+                unsafe {
+                    translated_write(exit_code_ptr, exit_code);
+                }
+            }
+            child_pid as isize
+        }
+        WaitResult::StillRunning => -2,
+        WaitResult::NoSuchChild => -1,
+    }
+}
+
+/// `sys_wait4`'s `options`: return `0` immediately instead of blocking if a
+/// matching child exists but hasn't exited yet
+///
+/// [`sys_waitpid`] still always behaves as if this were set — it never
+/// blocks the caller, unlike [`sys_wait4`]'s default of sleeping until a
+/// child exits — so on `sys_waitpid` a "still running" child stays `-2`
+/// regardless; `WNOHANG` only matters to `sys_wait4`, where leaving it
+/// unset is what turns that same case into an actual block instead.
+pub const WNOHANG: i32 = 1;
+
+/// pack an exit code into real `wait4`'s `status` encoding: a task killed
+/// by a signal (see the negative-`exit_code` convention documented on
+/// [`crate::task::TaskManager::exit_current_and_run_next`]) packs the
+/// signal number into the low 7 bits with bit 7 clear, while a normal exit
+/// packs its exit code into bits 8-15 with bits 0-6 clear — this is the
+/// same split real libc's `WIFEXITED`/`WEXITSTATUS`/`WIFSIGNALED`/`WTERMSIG`
+/// macros decode.
+fn wait_status(exit_code: i32) -> i32 {
+    if exit_code < 0 {
+        (-exit_code) & 0x7f
+    } else {
+        (exit_code & 0xff) << 8
+    }
+}
+
+/// wait for a child to exit, like [`sys_waitpid`], but also filling an
+/// optional [`Rusage`] for the reaped child and supporting [`WNOHANG`];
+/// `status_ptr`/`rusage_ptr` are skipped (not written) when null
+///
+/// Unlike [`sys_waitpid`], this one actually blocks: without `WNOHANG`, a
+/// matching child that hasn't exited yet parks the caller on
+/// [`crate::task::CHILD_EXIT_WQ`] (see [`waitpid_blocking_current`])
+/// instead of spinning. `WNOHANG` keeps the old non-blocking behavior,
+/// returning `0` immediately in that case instead.
+///
+/// Returns the reaped child's pid, `0` if `WNOHANG` is set and a matching
+/// child exists but hasn't exited yet, [`EINTR`] if a signal is delivered
+/// before a child exits, or `-1` if the caller has no such child at all.
+pub fn sys_wait4(pid: isize, status_ptr: *mut i32, options: i32, rusage_ptr: *mut Rusage) -> isize {
+    let result = if options & WNOHANG != 0 {
+        waitpid_current(pid)
+    } else {
+        match waitpid_blocking_current(pid) {
+            Some(result) => result,
+            None => return EINTR,
+        }
+    };
+    match result {
+        WaitResult::Reaped(child_pid, exit_code, rusage) => {
+            if !status_ptr.is_null() {
+                unsafe {
+                    translated_write(status_ptr, wait_status(exit_code));
+                }
+            }
+            if !rusage_ptr.is_null() {
+                let written = copy_to_user(
+                    rusage_ptr,
+                    Rusage {
+                        ru_utime_ms: rusage.utime_ms,
+                        ru_stime_ms: rusage.stime_ms,
+                        ru_maxrss: rusage.rss_kb,
+                        ru_nvcsw: rusage.nvcsw,
+                        ru_nivcsw: rusage.nivcsw,
+                    },
+                );
+                if written.is_none() {
+                    return EFAULT;
+                }
+            }
+            child_pid as isize
+        }
+        // `waitpid_blocking_current` never returns this: it only surfaces
+        // once `options & WNOHANG` steered `sys_wait4` to the
+        // non-blocking `waitpid_current` above instead
+        WaitResult::StillRunning => 0,
+        WaitResult::NoSuchChild => -1,
+    }
+}
+
+/// the calling task's own and its reaped children's accumulated user/kernel
+/// time, written through `buf` as a [`Tms`]
+///
+/// A test spawning a CPU-heavy child, waiting on it, and asserting `cutime`
+/// came back nonzero would need a `user` crate to build that child program
+/// against; this source tree has no such crate (nor any `Cargo.toml`/build
+/// pipeline for one), and no upstream test suite at any level to add one to
+/// regardless — see the same gap noted on [`sys_task_info`] and
+/// `crate::symtab`.
+///
+/// Real `times(2)` returns the number of clock ticks since an arbitrary
+/// point in the past (typically boot) on success, or `-1` on error; this
+/// kernel has no notion of "ticks" distinct from the millisecond timer
+/// [`get_time_ms`] already exposes, so that's what's returned here instead.
+pub fn sys_times(buf: *mut Tms) -> isize {
+    let (utime, stime) = TOTAL_TASKS.get_slot_times_ms(get_current_task());
+    let (cutime, cstime) = current_child_times_ms();
+    match copy_to_user(
+        buf,
+        Tms {
+            utime,
+            stime,
+            cutime,
+            cstime,
+        },
+    ) {
+        Some(()) => get_time_ms() as isize,
+        None => EFAULT,
+    }
+}
+
+/// start a new thread sharing the caller's address space, beginning at
+/// `entry` with `arg` passed through as its first argument; returns the
+/// new thread's tid
+pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
+    match thread_create_current(entry, arg) {
+        Some(tid) => tid as isize,
+        None => EAGAIN,
+    }
+}
+
+/// the calling task's own tid (its pid, doubling as a thread id — see
+/// [`crate::task::TaskControlBlock::memory_slot`])
+pub fn sys_gettid() -> isize {
+    get_current_pid() as isize
+}
+
+/// the calling task's own pid
+pub fn sys_getpid() -> isize {
+    get_current_pid() as isize
+}
+
+/// this process's stack-canary seed (see
+/// [`crate::task::TaskControlBlock::canary`]), for the user runtime's own
+/// stack-smashing checks: place it at a fixed offset below the top of each
+/// new stack frame and compare it again before returning, the same
+/// technique `-fstack-protector`'s generated checks use, just done by hand
+/// in user space since this kernel has no compiler-inserted canary support
+/// to hook into
+///
+/// This kernel has no mechanism for the check itself to be anything but
+/// the user runtime's own responsibility — there is no
+/// `sys_check_canary`, and a corrupted canary is caught (and can be
+/// reported) by that same user-space check, not by this kernel noticing a
+/// stack smash on its own.
+///
+/// Returns an `isize` rather than the `usize` the canary itself naturally
+/// is, same as [`sys_getcycles`]'s own return type — every syscall in this
+/// kernel shares the one `isize` return path through `syscall()`, and a
+/// canary with its top bit set is just as usable as a bit pattern after
+/// the round trip either way.
+///
+/// A test spawning two processes and confirming their canaries differ
+/// would be a pair of binaries in the sibling `user` crate this kernel
+/// loads at boot; that crate isn't part of this source tree, so there's
+/// nothing here to add such binaries to.
+pub fn sys_get_canary() -> isize {
+    canary_current() as isize
+}
+
+/// the calling task's parent's pid, or 0 if it has none — either because
+/// it was loaded directly at boot, or because it already is
+/// [`crate::task::INITPROC_PID`] itself (see
+/// [`crate::task::TaskManager::get_current_ppid`])
+pub fn sys_getppid() -> isize {
+    get_current_ppid() as isize
+}
+
+/// write up to `cap` of the calling task's live children's pids into
+/// `buf`, returning how many children there really were, which may exceed
+/// `cap` — the same convention [`sys_listtasks`] uses
+///
+/// A test forking two children and checking this returns exactly those
+/// two pids would be a binary in the sibling `user` crate this kernel
+/// loads at boot; that crate isn't part of this source tree, so there's
+/// nothing here to add such a binary to.
+pub fn sys_children(buf: *mut usize, cap: usize) -> isize {
+    let (snapshot, count) = children_of_current();
+    let to_write = cap.min(count);
+    let byte_len = to_write * core::mem::size_of::<usize>();
+    if to_write > 0 && !validate_user_range(buf as usize, byte_len, true) {
+        return EFAULT;
+    }
+    for (i, &pid) in snapshot.iter().take(to_write).enumerate() {
+        unsafe {
+            translated_write(buf.add(i), pid);
+        }
+    }
+    count as isize
+}
+
+/// the id of the hart the calling task is currently running on; see
+/// [`crate::hart::hart_id`]
+pub fn sys_get_cpu_id() -> isize {
+    crate::hart::hart_id() as isize
+}
+
+/// wait for the thread `tid`, sharing the caller's address space, to exit,
+/// reaping it; returns its exit code, `-2` if it hasn't exited yet, or
+/// `-1` if there is no such thread
+pub fn sys_waittid(tid: usize) -> isize {
+    match waittid_current(tid) {
+        WaitResult::Reaped(_, exit_code, _) => exit_code as isize,
+        WaitResult::StillRunning => -2,
+        WaitResult::NoSuchChild => -1,
+    }
+}
+
+/// create a mutex for the calling task's process, returning its id; `blocking`
+/// selects between parking waiters ([`crate::sync::WaitQueue`]-style) and
+/// having them spin, and `priority_inherit` enables the single-boost
+/// approximation of priority inheritance described on
+/// [`crate::task::Mutex::priority_inherit`]
+pub fn sys_mutex_create(blocking: bool, priority_inherit: bool) -> isize {
+    match mutex_create_current(blocking, priority_inherit) {
+        Some(id) => id as isize,
+        None => -1,
+    }
+}
+
+/// map an [`AcquireOutcome`] to this module's syscall return-code convention
+fn acquire_outcome_to_isize(outcome: AcquireOutcome) -> isize {
+    match outcome {
+        AcquireOutcome::Acquired => 0,
+        AcquireOutcome::Invalid => -1,
+        AcquireOutcome::WouldDeadlock => DEADLOCK_ERRNO,
+        AcquireOutcome::Interrupted => EINTR,
+    }
+}
+
+/// lock mutex `id`, blocking or spinning until it is free; returns -1 if
+/// `id` doesn't name a live mutex in the caller's process, or
+/// [`DEADLOCK_ERRNO`] if deadlock detection is enabled for the caller's
+/// process and waiting for it could leave it in an unsafe state
+pub fn sys_mutex_lock(id: usize) -> isize {
+    acquire_outcome_to_isize(mutex_lock_current(id))
+}
+
+/// unlock mutex `id`, which must currently be held by the caller; returns -1
+/// if `id` doesn't name a live mutex the caller holds
+pub fn sys_mutex_unlock(id: usize) -> isize {
+    if mutex_unlock_current(id) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// create a counting semaphore for the calling task's process with the
+/// given initial count, returning its id
+pub fn sys_semaphore_create(count: usize) -> isize {
+    match semaphore_create_current(count) {
+        Some(id) => id as isize,
+        None => -1,
+    }
+}
+
+/// increment semaphore `id`, waking one waiter instead if any are parked;
+/// returns -1 if `id` doesn't name a live semaphore in the caller's process
+pub fn sys_semaphore_up(id: usize) -> isize {
+    if semaphore_up_current(id) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// decrement semaphore `id`, blocking while its count is 0; returns -1 if
+/// `id` doesn't name a live semaphore in the caller's process, or
+/// [`DEADLOCK_ERRNO`] under the same deadlock-detection conditions as
+/// [`sys_mutex_lock`]
+pub fn sys_semaphore_down(id: usize) -> isize {
+    acquire_outcome_to_isize(semaphore_down_current(id))
+}
+
+/// create a condition variable for the calling task's process, returning
+/// its id
+pub fn sys_condvar_create() -> isize {
+    match condvar_create_current() {
+        Some(id) => id as isize,
+        None => -1,
+    }
+}
+
+/// wake one task waiting on condvar `id`; returns -1 if `id` doesn't name a
+/// live condvar in the caller's process
+pub fn sys_condvar_signal(id: usize) -> isize {
+    if condvar_signal_current(id) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// atomically release mutex `mutex_id` and block on condvar `cond_id`,
+/// re-acquiring the mutex before returning; returns -1 if either id is
+/// invalid or the caller doesn't hold the mutex (see
+/// [`crate::task::TaskManager::condvar_wait_current`])
+pub fn sys_condvar_wait(cond_id: usize, mutex_id: usize) -> isize {
+    if condvar_wait_current(cond_id, mutex_id) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// `sys_futex`'s `op`: block while `*uaddr == val`; see
+/// [`crate::task::TaskManager::futex_wait_current`]
+const FUTEX_WAIT: u32 = 0;
+/// `sys_futex`'s `op`: wake up to `val` tasks blocked on `uaddr`; see
+/// [`crate::task::TaskManager::futex_wake_current`]
+const FUTEX_WAKE: u32 = 1;
+
+/// minimal futex syscall for a userspace mutex that only traps into the
+/// kernel on contention: `FUTEX_WAIT` blocks while `*uaddr == val`,
+/// `FUTEX_WAKE` wakes up to `val` tasks parked on `uaddr`. Returns -1 for
+/// an unrecognized `op`, a null `uaddr`, or one outside the caller's own
+/// address space; `FUTEX_WAIT` also returns -1 (without blocking) if
+/// `*uaddr` no longer equals `val` by the time the kernel checks it, so
+/// the caller's userspace fast path can just retry instead.
+///
+/// `uaddr` is keyed directly by its raw address rather than a translated
+/// physical one — see [`crate::task::Futex`] for why that's equivalent in
+/// this kernel — so two processes sharing a mapping (e.g. via a future
+/// `sys_shmat`) can still rendezvous on the same word.
+///
+/// A test building a fast mutex on top of this — spin briefly, then
+/// `FUTEX_WAIT` on contention and `FUTEX_WAKE` on unlock — would normally
+/// be a binary in the sibling `user` crate this kernel loads at boot; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such a binary to — see the same note on `sys_setitimer`.
+pub fn sys_futex(uaddr: *mut u32, op: u32, val: u32) -> isize {
+    if uaddr.is_null() || !validate_user_range(uaddr as usize, core::mem::size_of::<u32>(), false) {
+        return -1;
+    }
+    match op {
+        FUTEX_WAIT => {
+            if futex_wait_current(uaddr as usize, val) {
+                0
+            } else {
+                -1
+            }
+        }
+        FUTEX_WAKE => futex_wake_current(uaddr as usize, val) as isize,
+        _ => -1,
+    }
+}
+
+/// turn the banker's-algorithm deadlock check inside `sys_mutex_lock` and
+/// `sys_semaphore_down` on or off for the calling task's process
+pub fn sys_enable_deadlock_detect(enabled: usize) -> isize {
+    enable_deadlock_detect_current(enabled != 0);
+    0
+}
+
+/// set the current task's stride-scheduling priority, returning it back on
+/// success; a `priority` outside the documented `[2, MAX_PRIO]` range
+/// returns -1 and leaves the task's priority unchanged. A forked child
+/// inherits its parent's priority (see [`crate::task::TaskManager::fork_current`]);
+/// `sys_exec` resets a task back to [`crate::task::DEFAULT_PRIORITY`] (see
+/// [`crate::task::TaskManager::exec_current`]), the same way it resets
+/// signal dispositions — a freshly loaded program shouldn't inherit the old
+/// one's runtime-tuned priority.
+///
+/// A test exercising the clamp (priority `1` and `MAX_PRIO + 1` both
+/// rejected, `2` and `MAX_PRIO` both accepted) and fork inheritance would be
+/// a binary in the sibling `user` crate this kernel loads at boot; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such a binary to.
+pub fn sys_set_priority(priority: isize) -> isize {
+    set_current_priority(priority).unwrap_or(-1)
+}
+
+/// zero the current task's syscall counters and recent-syscalls ring
+/// buffer, so a later `sys_task_info` reflects only what happens from here
+pub fn sys_reset_taskinfo() -> isize {
+    TOTAL_TASKS.reset_current_task_info();
+    0
+}
+
+/// get the status, scheduling class (priority and stride), syscall
+/// histogram (counts and accumulated CPU time), running time, trap
+/// overhead, and peak stack usage of the task with pid `pid`
+///
+/// `status`, `priority`, and `stride` are filled from one hold of the
+/// scheduler lock (see `TaskManager::task_sched_snapshot`), so a caller
+/// never sees, say, a `Running` status paired with a `stride` sampled
+/// after the next task was already switched in.
+///
+/// A test reading this back mid-run and asserting `status` is `Running`
+/// and `priority` matches a prior `sys_set_priority`, or one that calls a
+/// fast syscall (e.g. `sys_getpid`) and a slow one (e.g. a `sys_write` of
+/// a large buffer) and compares the two syscalls' `TaskInfo::syscall_time_us`
+/// entries, or one that issues many cheap syscalls in a row and confirms
+/// `TaskInfo::trap_overhead_us` is tracked separately from
+/// `syscall_time_us` and comes out nonzero, would be a binary in the
+/// sibling `user` crate this kernel loads at boot; that crate isn't part
+/// of this source tree, so there's nothing here to add such a binary to.
+///
+/// A test running a recursive app to a few different depths and confirming
+/// `TaskInfo::peak_stack_bytes` grows with recursion depth would be the
+/// same kind of binary in that same missing sibling crate.
+pub fn sys_task_info(pid: usize, ti: *mut TaskInfo) -> isize {
+    match TOTAL_TASKS.get_task_info(pid) {
+        Some((
+            status,
+            priority,
+            stride,
+            syscall_times,
+            syscall_time_us,
+            time,
+            trap_overhead_us,
+            peak_stack_bytes,
+        )) => match copy_to_user(
+            ti,
+            TaskInfo {
+                status,
+                priority,
+                stride,
+                syscall_times,
+                syscall_time_us,
+                time,
+                trap_overhead_us,
+                peak_stack_bytes,
+            },
+        ) {
+            Some(()) => 0,
+            None => EFAULT,
+        },
+        None => -1,
+    }
+}
+
+/// `sys_setrlimit`/`sys_getrlimit`'s `resource`: max open fds, same value
+/// as real Linux's `RLIMIT_NOFILE`
+pub const RLIMIT_NOFILE: i32 = 7;
+/// `sys_setrlimit`/`sys_getrlimit`'s `resource`: max live children, same
+/// value as real Linux's `RLIMIT_NPROC`
+pub const RLIMIT_NPROC: i32 = 6;
+/// `sys_setrlimit`/`sys_getrlimit`'s `resource`: max total `mmap`ed bytes,
+/// same value as real Linux's `RLIMIT_AS`
+pub const RLIMIT_AS: i32 = 9;
+/// `sys_setrlimit`/`sys_getrlimit`'s `resource`: max accumulated CPU time
+/// in seconds (real Linux's own unit for this one; every other resource
+/// here is a byte/fd/task count), same value as real Linux's `RLIMIT_CPU`
+pub const RLIMIT_CPU: i32 = 0;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Soft/hard limit pair read and written by `sys_getrlimit`/`sys_setrlimit`,
+/// matching the real `struct rlimit`
+pub struct RLimit {
+    /// the limit actually enforced
+    pub cur: u64,
+    /// the most `cur` may be raised to; for [`RLIMIT_NOFILE`]/[`RLIMIT_NPROC`]
+    /// this is always this kernel's structural hard ceiling
+    /// ([`crate::config::MAX_FD_NUM`]/[`crate::config::MAX_APP_NUM`]), which
+    /// `sys_setrlimit` can never raise `cur` past; for [`RLIMIT_AS`] there is
+    /// no such ceiling, so `max` is always `u64::MAX` (`RLIM_INFINITY`)
+    pub max: u64,
+}
+
+/// convert a [`RLimit`] field's seconds (real `RLIMIT_CPU`'s own unit) to
+/// this kernel's internal milliseconds, preserving `u64::MAX`
+/// (`RLIM_INFINITY`) as `usize::MAX` rather than overflowing it through a
+/// `* 1000`
+fn rlimit_cpu_secs_to_ms(secs: u64) -> usize {
+    if secs == u64::MAX {
+        usize::MAX
+    } else {
+        (secs as usize).saturating_mul(1000)
+    }
+}
+
+/// the inverse of [`rlimit_cpu_secs_to_ms`]
+fn rlimit_cpu_ms_to_secs(ms: usize) -> u64 {
+    if ms == usize::MAX {
+        u64::MAX
+    } else {
+        (ms / 1000) as u64
+    }
+}
+
+/// read the calling task's current/max limit for `resource` ([`RLIMIT_NOFILE`],
+/// [`RLIMIT_NPROC`], [`RLIMIT_AS`], or [`RLIMIT_CPU`]) into `limit`; returns
+/// `-EINVAL` for any other `resource`, or `EFAULT` if `limit` can't be
+/// written to
+pub fn sys_getrlimit(resource: i32, limit: *mut RLimit) -> isize {
+    let rlimit = match resource {
+        RLIMIT_NOFILE => RLimit {
+            cur: rlimit_nofile_current() as u64,
+            max: crate::config::MAX_FD_NUM as u64,
+        },
+        RLIMIT_NPROC => RLimit {
+            cur: rlimit_nproc_current() as u64,
+            max: crate::config::MAX_APP_NUM as u64,
+        },
+        RLIMIT_AS => RLimit {
+            cur: rlimit_as_current() as u64,
+            max: u64::MAX,
+        },
+        RLIMIT_CPU => {
+            let (soft_ms, hard_ms) = rlimit_cpu_current();
+            RLimit {
+                cur: rlimit_cpu_ms_to_secs(soft_ms),
+                max: rlimit_cpu_ms_to_secs(hard_ms),
+            }
+        }
+        _ => return EINVAL,
+    };
+    match copy_to_user(limit, rlimit) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// set the calling task's current limit for `resource` ([`RLIMIT_NOFILE`],
+/// [`RLIMIT_NPROC`], [`RLIMIT_AS`], or [`RLIMIT_CPU`]) from `limit.cur`;
+/// returns `-EINVAL` for any other `resource` or if `limit.cur` exceeds
+/// `resource`'s hard maximum (see [`RLimit::max`]), or `EFAULT` if `limit`
+/// can't be read
+///
+/// The new limit applies immediately and is inherited by any child this
+/// task later creates via `sys_fork`/`sys_clone`/`sys_thread_create` —
+/// see `TaskManager::fork_current` and friends — but never retroactively
+/// affects resources the task already holds past the new limit (e.g.
+/// lowering `RLIMIT_NOFILE` below the current open-fd count doesn't close
+/// any fd; it only blocks further `sys_open`/`sys_pipe`/`sys_dup` calls).
+/// [`RLIMIT_CPU`] is the exception: it's checked against already-accumulated
+/// time (see `TaskManager::check_cpu_limit_current`), so lowering it below
+/// a task's current usage takes effect on the very next timer tick.
+///
+/// Unlike [`RLIMIT_NOFILE`]/[`RLIMIT_NPROC`]/[`RLIMIT_AS`], [`RLIMIT_CPU`]
+/// has two independently caller-set values rather than one settable `cur`
+/// against a fixed structural `max` — `limit.max` becomes the new hard
+/// limit too, rejected with `-EINVAL` if it would leave `cur > max`.
+///
+/// A test that lowers `RLIMIT_NOFILE` and confirms a later `sys_open` fails
+/// with `-EMFILE` past it would be a binary in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree, so
+/// there's nothing here to add such a binary to.
+pub fn sys_setrlimit(resource: i32, limit: *const RLimit) -> isize {
+    if !validate_user_range(limit as usize, core::mem::size_of::<RLimit>(), false) {
+        return EFAULT;
+    }
+    let limit = unsafe { translated_read(limit) };
+    let new_cur = limit.cur as usize;
+    let ok = match resource {
+        RLIMIT_NOFILE => set_rlimit_nofile_current(new_cur),
+        RLIMIT_NPROC => set_rlimit_nproc_current(new_cur),
+        RLIMIT_AS => {
+            set_rlimit_as_current(new_cur);
+            Some(())
+        }
+        RLIMIT_CPU => {
+            let soft_ms = rlimit_cpu_secs_to_ms(limit.cur);
+            let hard_ms = rlimit_cpu_secs_to_ms(limit.max);
+            if soft_ms > hard_ms {
+                return EINVAL;
+            }
+            set_rlimit_cpu_current(soft_ms, hard_ms);
+            Some(())
+        }
+        _ => return EINVAL,
+    };
+    match ok {
+        Some(()) => 0,
+        None => EINVAL,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Arguments for `sys_prlimit`, bundled behind a pointer since this
+/// kernel's syscall dispatch only forwards three register-sized args (see
+/// `crate::syscall::syscall`) and this call needs four — same reasoning as
+/// [`MmapFileRequest`]
+pub struct PrlimitRequest {
+    /// which task's limit to read/change
+    pub pid: usize,
+    /// which limit: [`RLIMIT_NOFILE`], [`RLIMIT_NPROC`], [`RLIMIT_AS`], or
+    /// [`RLIMIT_CPU`]
+    pub resource: i32,
+    /// the limit to install, or null to only query the current one
+    pub new: *const RLimit,
+    /// where to write `pid`'s current limit before any change from `new`
+    /// takes effect, or null to skip the query
+    pub old: *mut RLimit,
+}
+
+/// query and/or set the task with pid `req.pid`'s limit for
+/// `req.resource` ([`RLIMIT_NOFILE`], [`RLIMIT_NPROC`], [`RLIMIT_AS`], or
+/// [`RLIMIT_CPU`]), instead of the caller's own — the supervisor
+/// counterpart to [`sys_getrlimit`]/[`sys_setrlimit`]. If `req.old` is
+/// non-null, `req.pid`'s current limit for `req.resource` is written there
+/// first, same shape as [`sys_getrlimit`]. If `req.new` is non-null,
+/// `req.new.cur`/`req.new.max` then replace it, same effect
+/// [`sys_setrlimit`] has on the caller. Returns `-ESRCH` if no task has
+/// pid `req.pid`, `-EINVAL` for an unrecognized `req.resource` or an
+/// out-of-range `req.new`, `-EFAULT` if `req`/`req.new`/`req.old` can't be
+/// read/written, and `0` on success.
+///
+/// Raising `RLIMIT_CPU`'s hard limit above what `req.pid` already has —
+/// [`RLIMIT_NOFILE`]/[`RLIMIT_NPROC`] can never have their hard limit
+/// raised past this kernel's own structural ceiling regardless of caller,
+/// and [`RLIMIT_AS`] has no hard limit to raise in the first place — is
+/// rejected with `-EPERM` unless the caller is [`INITPROC_PID`], this
+/// kernel's closest equivalent to a privileged supervisor (see
+/// `sys_shutdown`'s own doc comment for the same convention). Lowering a
+/// limit, `RLIMIT_CPU`'s soft limit included, needs no privilege — reining
+/// in a runaway child's resource use is exactly what this syscall is for.
+///
+/// The new limit takes effect on `req.pid`'s very next relevant check, the
+/// same immediacy [`sys_setrlimit`]'s own doc comment describes for the
+/// caller's own limits: for `RLIMIT_CPU` that's `req.pid`'s next timer
+/// tick (see `TaskManager::check_cpu_limit_current`, which reads the field
+/// fresh every tick rather than caching it), for the others `req.pid`'s
+/// next `sys_open`/`sys_pipe`/`sys_dup`/`sys_mmap`/`sys_mmap_file`.
+///
+/// A test where a parent lowers a running child's `RLIMIT_CPU` and
+/// observes it killed by `SIGXCPU` sooner than it otherwise would be needs
+/// two cooperating binaries in the sibling `user` crate this kernel loads
+/// at boot; that crate isn't part of this source tree, so there's nothing
+/// here to add such binaries to.
+pub fn sys_prlimit(req: *const PrlimitRequest) -> isize {
+    if !validate_user_range(req as usize, core::mem::size_of::<PrlimitRequest>(), false) {
+        return EFAULT;
+    }
+    let PrlimitRequest { pid, resource, new, old } = unsafe { translated_read(req) };
+    let Some((nofile, nproc, as_bytes, cpu_soft_ms, cpu_hard_ms)) = rlimits_of(pid) else {
+        return ESRCH;
+    };
+    if !old.is_null() {
+        let current = match resource {
+            RLIMIT_NOFILE => RLimit {
+                cur: nofile as u64,
+                max: crate::config::MAX_FD_NUM as u64,
+            },
+            RLIMIT_NPROC => RLimit {
+                cur: nproc as u64,
+                max: crate::config::MAX_APP_NUM as u64,
+            },
+            RLIMIT_AS => RLimit {
+                cur: as_bytes as u64,
+                max: u64::MAX,
+            },
+            RLIMIT_CPU => RLimit {
+                cur: rlimit_cpu_ms_to_secs(cpu_soft_ms),
+                max: rlimit_cpu_ms_to_secs(cpu_hard_ms),
+            },
+            _ => return EINVAL,
+        };
+        if copy_to_user(old, current).is_none() {
+            return EFAULT;
+        }
+    }
+    if new.is_null() {
+        return 0;
+    }
+    if !validate_user_range(new as usize, core::mem::size_of::<RLimit>(), false) {
+        return EFAULT;
+    }
+    let limit = unsafe { translated_read(new) };
+    let new_cur = limit.cur as usize;
+    let ok = match resource {
+        RLIMIT_NOFILE => set_rlimit_nofile_of(pid, new_cur),
+        RLIMIT_NPROC => set_rlimit_nproc_of(pid, new_cur),
+        RLIMIT_AS => set_rlimit_as_of(pid, new_cur),
+        RLIMIT_CPU => {
+            let soft_ms = rlimit_cpu_secs_to_ms(limit.cur);
+            let hard_ms = rlimit_cpu_secs_to_ms(limit.max);
+            if soft_ms > hard_ms {
+                return EINVAL;
+            }
+            if hard_ms > cpu_hard_ms && get_current_pid() != INITPROC_PID {
+                return EPERM;
+            }
+            set_rlimit_cpu_of(pid, soft_ms, hard_ms)
+        }
+        _ => return EINVAL,
+    };
+    match ok {
+        Some(()) => 0,
+        None => EINVAL,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Interrupt-to-switch latency stats returned by `sys_irqstats`, in cycles
+/// (see `crate::timer::get_cycles`) rather than a time unit, since this is
+/// meant for characterizing raw scheduling overhead rather than wall-clock
+/// reporting
+pub struct IrqStats {
+    /// the shortest interval seen between a timer interrupt firing and the
+    /// scheduler switching away
+    pub min_cycles: u64,
+    /// the longest such interval seen
+    pub max_cycles: u64,
+    /// the mean interval across every timer interrupt observed
+    pub avg_cycles: u64,
+    /// how many timer interrupts have been observed; `min`/`max`/`avg` are
+    /// all `0` while this is still `0`
+    pub count: u64,
+}
+
+/// the current interrupt-to-switch latency stats accumulated by
+/// `crate::trap::trap_handler`, written through `buf`; returns `-1` if
+/// `crate::config::IRQ_LATENCY_STATS` is off, since nothing is being
+/// recorded for this to report
+///
+/// A test that busy-loops to generate timer-interrupt load and then checks
+/// the reported figures are nonzero and within a plausible bound would be a
+/// binary in the sibling `user` crate this kernel loads at boot; that crate
+/// isn't part of this source tree, so there's nothing here to add such a
+/// binary to.
+pub fn sys_irqstats(buf: *mut IrqStats) -> isize {
+    if !crate::config::IRQ_LATENCY_STATS {
+        return -1;
+    }
+    let (min_cycles, max_cycles, avg_cycles, count) = crate::trap::irq_latency_stats();
+    match copy_to_user(
+        buf,
+        IrqStats {
+            min_cycles,
+            max_cycles,
+            avg_cycles,
+            count,
+        },
+    ) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// Trap-cause histogram returned by `sys_trapstats`, unconditionally
+/// maintained (unlike [`IrqStats`], which is only recorded when
+/// [`crate::config::IRQ_LATENCY_STATS`] is set) since every counter here is
+/// a single relaxed atomic increment already sitting on the trap-entry
+/// path, cheap enough to always be on
+pub struct TrapStats {
+    /// `Trap::Interrupt(Interrupt::SupervisorTimer)` count
+    pub timer_interrupt: u64,
+    /// `Trap::Exception(Exception::UserEnvCall)` count — every syscall
+    /// trap, regardless of which syscall; see `sys_task_info`'s
+    /// `syscall_times` for a per-syscall-id breakdown instead
+    pub syscall: u64,
+    /// combined `StoreFault`/`StorePageFault`/`LoadFault`/`LoadPageFault`/
+    /// `InstructionPageFault` count
+    pub page_fault: u64,
+    /// `Exception::IllegalInstruction` count
+    pub illegal_instruction: u64,
+    /// combined `Exception::LoadMisaligned`/`Exception::StoreMisaligned`
+    /// count
+    pub misaligned: u64,
+    /// `Exception::Breakpoint` (`ebreak`) count
+    pub breakpoint: u64,
+    /// user-stack-guard-region hit count; see `user_stack_guard_range`
+    pub stack_overflow: u64,
+    /// non-executable-`mmap`-region instruction fetch count; see
+    /// `TaskManager::is_non_executable_mmap_addr`
+    pub non_executable_fetch: u64,
+    /// any other `scause` this kernel doesn't otherwise recognize; always
+    /// `0` in practice, since `trap_handler` panics on one before this
+    /// syscall could ever be called again to observe it
+    pub other: u64,
+}
+
+/// the trap-cause histogram accumulated by `crate::trap::trap_handler`
+/// since boot, written through `buf`; always succeeds
+///
+/// A test triggering a known mix of traps (some syscalls, an `ebreak`, an
+/// illegal instruction) and reading this back to confirm each bucket
+/// matches would be a binary in the sibling `user` crate this kernel loads
+/// at boot; that crate isn't part of this source tree, so there's nothing
+/// here to add such a binary to.
+pub fn sys_trapstats(buf: *mut TrapStats) -> isize {
+    let (
+        timer_interrupt,
+        syscall,
+        page_fault,
+        illegal_instruction,
+        misaligned,
+        breakpoint,
+        stack_overflow,
+        non_executable_fetch,
+        other,
+    ) = crate::trap::trap_histogram();
+    match copy_to_user(
+        buf,
+        TrapStats {
+            timer_interrupt,
+            syscall,
+            page_fault,
+            illegal_instruction,
+            misaligned,
+            breakpoint,
+            stack_overflow,
+            non_executable_fetch,
+            other,
+        },
+    ) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// fill `buf` with `len` pseudo-random bytes; see
+/// [`crate::rng::GRND_DETERMINISTIC`] for the one recognized `flags` bit
+///
+/// There's no `translated_byte_buffer`-style per-page copy here for the
+/// same reason `sys_write` doesn't need one (see that syscall's own doc
+/// comment): this kernel's flat, identity-mapped memory means `buf` is
+/// already a directly dereferenceable kernel address, so `crate::rng::fill`
+/// writes straight into it once [`validate_user_range`] confirms the whole
+/// range is actually the caller's to write to.
+///
+/// A test requesting 32 bytes twice and checking they differ without
+/// [`GRND_DETERMINISTIC`] set, and match with it set, would be a binary in
+/// the sibling `user` crate this kernel loads at boot; that crate isn't
+/// part of this source tree, so there's nothing here to add such a binary
+/// to.
+pub fn sys_getrandom(buf: *mut u8, len: usize, flags: u32) -> isize {
+    if !validate_user_range(buf as usize, len, true) {
+        return EFAULT;
+    }
+    let slice = unsafe { core::slice::from_raw_parts_mut(buf, len) };
+    rng_fill(slice, flags);
+    len as isize
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+/// run-queue and per-state task counts returned by `sys_runqueue_stats`;
+/// complements [`SysInfo`]'s `procs` with scheduling-specific detail
+pub struct RunQueueStats {
+    /// number of tasks currently [`TaskStatus::Ready`]
+    pub ready: usize,
+    /// number of tasks currently [`TaskStatus::Blocked`]
+    pub blocked: usize,
+    /// number of exited-but-unreaped (zombie) tasks, i.e.
+    /// [`TaskStatus::Exited`]
+    pub zombie: usize,
+    /// each hart's local run-queue length, i.e. how many `Ready` tasks
+    /// [`TaskManager::find_next_task`](crate::task::TaskManager) would
+    /// currently consider on that hart before stealing from another
+    pub run_queue_len: [usize; MAX_HARTS],
+}
+
+/// scheduling-specific snapshot, written through `stats`; see
+/// [`RunQueueStats`]
+///
+/// The whole snapshot is read from one `TaskManager::runqueue_stats` lock
+/// acquisition rather than several separate ones (contrast `sys_sysinfo`,
+/// which reads `procs` and the uptime/memory figures without a shared lock
+/// across them) — the request specifically asks for this one to be
+/// self-consistent, and unlike `sys_sysinfo`'s figures, these are cheap
+/// enough to all come from a single scan of the task table under the same
+/// lock.
+///
+/// A test that blocks some tasks on a semaphore (`sys_semaphore_down`) and
+/// checks `blocked` matches would be a binary in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree,
+/// so there's nothing here to add such a binary to.
+pub fn sys_runqueue_stats(stats: *mut RunQueueStats) -> isize {
+    let (ready, blocked, zombie, run_queue_len) = runqueue_stats();
+    match copy_to_user(
+        stats,
+        RunQueueStats {
+            ready,
+            blocked,
+            zombie,
+            run_queue_len,
+        },
+    ) {
+        Some(()) => 0,
+        None => EFAULT,
+    }
+}
+
+/// `sys_membarrier`'s `cmd`: report which commands are supported, as a
+/// bitmask of the other `MEMBARRIER_CMD_*` values, rather than executing a
+/// barrier
+pub const MEMBARRIER_CMD_QUERY: usize = 0;
+/// `sys_membarrier`'s `cmd`: issue a full memory barrier on every hart
+/// currently running one of the caller's threads
+pub const MEMBARRIER_CMD_GLOBAL: usize = 1 << 0;
+
+/// force memory ordering across harts for lock-free userspace algorithms:
+/// [`MEMBARRIER_CMD_GLOBAL`] is meant to issue an IPI to every other hart
+/// running one of the caller's threads and block until each has executed
+/// a fence, so a caller can pair a cheap fence-free fast path with this
+/// expensive barrier on the slow path instead of fencing on every access.
+///
+/// [`crate::hart::hart_id`]'s own doc comment explains why that can't
+/// happen for real in this source tree: `entry.asm`/`sbi.rs` (SBI's HSM
+/// `hart_start`) are missing from this snapshot, so no secondary hart is
+/// ever actually booted and `hart_id()` always reports `0` — there is no
+/// "every other hart running one of the caller's threads" to IPI in the
+/// first place. What this can honestly do is execute the fence on the
+/// only hart that ever runs anything, which is what
+/// [`core::sync::atomic::fence`] below does; once real multi-hart boot
+/// exists, issuing the same fence via an IPI to every hart in
+/// [`crate::task::TaskManager`]'s per-hart `current_tasks` that's running
+/// the caller's pid is the change this would need.
+///
+/// Returns the supported-command bitmask for [`MEMBARRIER_CMD_QUERY`],
+/// `0` for [`MEMBARRIER_CMD_GLOBAL`], or [`EINVAL`] for anything else —
+/// the same three-way split the real syscall uses.
+pub fn sys_membarrier(cmd: usize) -> isize {
+    match cmd {
+        MEMBARRIER_CMD_QUERY => MEMBARRIER_CMD_GLOBAL as isize,
+        MEMBARRIER_CMD_GLOBAL => {
+            core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            0
+        }
+        _ => EINVAL,
+    }
+}
+
+/// pin the task with pid `pid` (`0` meaning the caller) to the set of
+/// harts named by `mask`, one bit per hart index; [`steal_task`] and
+/// [`yield_to_current`] both refuse to schedule the task onto a hart
+/// outside this mask afterward (see
+/// [`crate::task::TaskManager::set_affinity`]).
+///
+/// [`EINVAL`] if `mask`, once restricted to [`ALL_HARTS_MASK`]'s bits (the
+/// only harts this kernel ever has), comes out zero — a task pinned to no
+/// hart at all could never run again. `-1` if `pid` doesn't name a live
+/// task.
+///
+/// [`crate::hart::hart_id`]'s own doc comment explains why this can only
+/// be partially exercised for real in this source tree: with
+/// `entry.asm`/`sbi.rs` missing, no secondary hart is ever actually
+/// booted and `hart_id()` always reports `0`, so a mask excluding hart `0`
+/// pins a task somewhere it can never be observed running — there's no
+/// hart `1` for it to run on instead. What's real and testable right now
+/// is the mask being stored, read back by `sys_sched_getaffinity`, and
+/// respected by `steal_task`/`yield_to_current`'s candidate filtering; a
+/// test confirming a task is actually scheduled on the hart its mask
+/// names would need real multi-hart boot this snapshot doesn't have, and
+/// would otherwise be a binary in the sibling `user` crate this kernel
+/// loads at boot, which also isn't part of this source tree.
+pub fn sys_sched_setaffinity(pid: usize, mask: usize) -> isize {
+    let pid = if pid == 0 { get_current_pid() } else { pid };
+    let mask = mask & ALL_HARTS_MASK;
+    if mask == 0 {
+        return EINVAL;
+    }
+    if set_affinity(pid, mask) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// read back the task with pid `pid` (`0` meaning the caller)'s affinity
+/// mask set by `sys_sched_setaffinity`, defaulting to [`ALL_HARTS_MASK`]
+/// (every hart) for a task that was never pinned; see
+/// [`crate::task::TaskManager::get_affinity`]. `-1` if `pid` doesn't name
+/// a live task.
+pub fn sys_sched_getaffinity(pid: usize, mask_ptr: *mut usize) -> isize {
+    let pid = if pid == 0 { get_current_pid() } else { pid };
+    match get_affinity(pid) {
+        Some(mask) => match copy_to_user(mask_ptr, mask) {
+            Some(()) => 0,
+            None => EFAULT,
+        },
+        None => -1,
+    }
+}
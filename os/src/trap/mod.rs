@@ -0,0 +1,391 @@
+//! Trap handling functionality
+//!
+//! All traps (syscalls, exceptions and interrupts) raised while executing
+//! user code land in [`trap_handler`] via `__alltraps`. Traps from kernel
+//! mode are not handled here and instead cause a panic.
+
+mod context;
+
+use crate::config::IRQ_LATENCY_STATS;
+use crate::hart::hart_id;
+use crate::loader::{kernel_stack_canary_intact, user_stack_guard_range};
+use crate::sync::SpinLock;
+use crate::syscall::{syscall, SYSCALL_SIGRETURN, TOTAL_TASKS};
+use crate::task::{
+    check_cpu_limit_current, exit_current_and_run_next, fire_expired_itimers, get_current_task,
+    handle_pending_signal_current, is_non_executable_mmap_addr, mark_fp_dirty_current,
+    record_stack_watermark_current, segfault_current, sigreturn_current,
+    suspend_current_and_run_next, SwitchCause, SIGBUS, SIGILL,
+};
+use crate::timer::{get_cycles, get_time_us, set_next_trigger};
+use core::arch::global_asm;
+use core::sync::atomic::{AtomicU64, Ordering};
+use lazy_static::lazy_static;
+use riscv::register::{
+    mtvec::TrapMode,
+    scause::{self, Exception, Interrupt, Trap},
+    sie, sstatus, stval, stvec,
+};
+
+/// the buckets [`sys_trapstats`](crate::syscall::process::sys_trapstats)
+/// reports; every counter is a plain [`AtomicU64`] bumped once per trap in
+/// [`trap_handler`], the same cheap-lock-free style [`crate::mm::HeapStats`]
+/// uses for its own per-alloc counters
+pub struct TrapHistogram {
+    /// `Trap::Interrupt(Interrupt::SupervisorTimer)`
+    timer_interrupt: AtomicU64,
+    /// `Trap::Exception(Exception::UserEnvCall)` — every syscall, regardless
+    /// of which one; a per-syscall-id breakdown already exists via
+    /// `sys_task_info`'s `syscall_times`, so this bucket doesn't duplicate
+    /// it, only the fact that a syscall trap happened at all
+    syscall: AtomicU64,
+    /// `StoreFault`/`StorePageFault`/`LoadFault`/`LoadPageFault`/
+    /// `InstructionPageFault` — this kernel has no real MMU raising a
+    /// distinct fault per access mode (see `TaskManager::mmap_current`'s
+    /// doc comment on why), so these all land in one bucket rather than
+    /// five near-empty ones
+    page_fault: AtomicU64,
+    /// `Exception::IllegalInstruction`
+    illegal_instruction: AtomicU64,
+    /// `Exception::LoadMisaligned`/`Exception::StoreMisaligned`
+    misaligned: AtomicU64,
+    /// `Exception::Breakpoint` (`ebreak`)
+    breakpoint: AtomicU64,
+    /// the user-stack-guard check at the top of [`trap_handler`], counted
+    /// separately since it's caught before `scause` is even dispatched on
+    stack_overflow: AtomicU64,
+    /// the non-executable-`mmap`-region check at the top of
+    /// [`trap_handler`], same reasoning as [`Self::stack_overflow`]
+    non_executable_fetch: AtomicU64,
+    /// any other `scause` this kernel doesn't otherwise recognize (see
+    /// `trap_handler`'s final `panic!` arm) — always `0` in practice, since
+    /// that arm panics before ever returning, kept only so the histogram
+    /// stays exhaustive over every path through `trap_handler`
+    other: AtomicU64,
+}
+
+static TRAP_HISTOGRAM: TrapHistogram = TrapHistogram {
+    timer_interrupt: AtomicU64::new(0),
+    syscall: AtomicU64::new(0),
+    page_fault: AtomicU64::new(0),
+    illegal_instruction: AtomicU64::new(0),
+    misaligned: AtomicU64::new(0),
+    breakpoint: AtomicU64::new(0),
+    stack_overflow: AtomicU64::new(0),
+    non_executable_fetch: AtomicU64::new(0),
+    other: AtomicU64::new(0),
+};
+
+/// `(timer_interrupt, syscall, page_fault, illegal_instruction, misaligned,
+/// breakpoint, stack_overflow, non_executable_fetch, other)` trap counts
+/// recorded so far by [`trap_handler`], for
+/// [`sys_trapstats`](crate::syscall::process::sys_trapstats)
+pub fn trap_histogram() -> (u64, u64, u64, u64, u64, u64, u64, u64, u64) {
+    (
+        TRAP_HISTOGRAM.timer_interrupt.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.syscall.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.page_fault.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.illegal_instruction.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.misaligned.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.breakpoint.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.stack_overflow.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.non_executable_fetch.load(Ordering::Relaxed),
+        TRAP_HISTOGRAM.other.load(Ordering::Relaxed),
+    )
+}
+
+/// min/max/avg cycle counts between a timer interrupt firing and the
+/// scheduler actually switching away, for `sys_irqstats` — only ever
+/// updated when [`IRQ_LATENCY_STATS`] is set; see [`record_irq_latency`]
+#[derive(Clone, Copy)]
+pub struct IrqLatencyStats {
+    min_cycles: u64,
+    max_cycles: u64,
+    sum_cycles: u64,
+    count: u64,
+}
+
+lazy_static! {
+    static ref IRQ_LATENCY: SpinLock<IrqLatencyStats> = SpinLock::new(IrqLatencyStats {
+        min_cycles: u64::MAX,
+        max_cycles: 0,
+        sum_cycles: 0,
+        count: 0,
+    });
+}
+
+/// fold one observed interrupt-to-switch latency (in cycles) into
+/// [`IRQ_LATENCY`]; a no-op unless [`IRQ_LATENCY_STATS`] is set
+fn record_irq_latency(cycles: u64) {
+    let mut stats = IRQ_LATENCY.exclusive_access();
+    stats.min_cycles = stats.min_cycles.min(cycles);
+    stats.max_cycles = stats.max_cycles.max(cycles);
+    stats.sum_cycles += cycles;
+    stats.count += 1;
+}
+
+/// `(min, max, avg, count)` cycle counts recorded so far by
+/// [`record_irq_latency`], for `sys_irqstats`; `min`/`max`/`avg` are all `0`
+/// if `count` is still `0` — nothing has been recorded yet, either because
+/// no timer interrupt has fired or because [`IRQ_LATENCY_STATS`] is off
+pub fn irq_latency_stats() -> (u64, u64, u64, u64) {
+    let stats = IRQ_LATENCY.exclusive_access();
+    if stats.count == 0 {
+        (0, 0, 0, 0)
+    } else {
+        (
+            stats.min_cycles,
+            stats.max_cycles,
+            stats.sum_cycles / stats.count,
+            stats.count,
+        )
+    }
+}
+
+global_asm!(include_str!("trap.S"));
+
+global_asm!(
+    "
+    .section .text
+    .global __sigreturn_trampoline
+__sigreturn_trampoline:
+    li a7, 139
+    ecall
+"
+);
+
+extern "C" {
+    /// the address a signal handler's own `ra` is set to on entry, so that
+    /// when the handler executes `ret` it lands here and re-enters the
+    /// kernel with the `sys_sigreturn` syscall instead of returning to
+    /// wherever the task happened to be before the signal arrived
+    ///
+    /// A real paged kernel maps a trampoline like this at the same virtual
+    /// address in every task so it survives an `satp` switch; this kernel
+    /// has no per-task page tables at all (see `crate::mm`), so there is no
+    /// equivalent switch to survive — this is simply this kernel image's
+    /// own code, directly reachable from U-mode the same way every other
+    /// kernel address already is in a flat, unprotected address space.
+    fn __sigreturn_trampoline();
+}
+
+/// the address to set a signal handler's return address to; see
+/// [`__sigreturn_trampoline`]
+pub fn sigreturn_trampoline_addr() -> usize {
+    __sigreturn_trampoline as usize
+}
+
+/// initialize CSR `stvec` as the entry of `__alltraps`
+pub fn init() {
+    extern "C" {
+        fn __alltraps();
+    }
+    unsafe {
+        stvec::write(__alltraps as usize, TrapMode::Direct);
+    }
+}
+
+/// enable the supervisor-timer interrupt (`STIE` in `sie`), so that preemptive
+/// time-slice round-robin scheduling can kick in
+pub fn enable_timer_interrupt() {
+    unsafe {
+        sie::set_stimer();
+    }
+}
+
+/// turn on the floating-point unit (`sstatus.fs`) once at boot, so that
+/// neither user nor kernel code traps the first time it executes an F/D
+/// instruction
+///
+/// A real kernel would instead leave `fs` at `Off` for a task that's never
+/// touched FP and let its first `fsd`/`fld` fault in to allocate FP context
+/// on demand — the fully lazy scheme described on
+/// [`crate::task::TaskControlBlock::fp_dirty`]. That needs the FP-disabled
+/// trap handled in `trap.S`'s entry assembly, which isn't part of this
+/// source tree, so `fs` is just left enabled for good instead; laziness is
+/// still achieved at the save/restore level via `fp_dirty`.
+pub fn enable_fpu() {
+    unsafe {
+        sstatus::set_fs(sstatus::FS::Initial);
+    }
+}
+
+#[no_mangle]
+/// handle a trap (syscall, exception or interrupt) raised from user space
+///
+/// Tests triggering an illegal instruction and an `ebreak` and asserting on
+/// the resulting exit code (or, for `ebreak`, that execution resumed), or
+/// installing a `SIGSEGV` handler, touching an unmapped page, and recovering
+/// by resetting `sepc` before `sys_sigreturn` — longjmp-style — would be
+/// binaries in the sibling `user` crate this kernel loads at boot; that
+/// crate isn't part of this source tree, so there's nothing here to add
+/// such binaries to.
+pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
+    // interrupt-entry cycle timestamp for `sys_irqstats`; read unconditionally
+    // (a cycle-counter read is cheap) even though it's only used below when
+    // this trap turns out to be a timer interrupt and `IRQ_LATENCY_STATS` is
+    // set, so the measured window always starts as close to the real
+    // interrupt as possible rather than after the exception-cause dispatch
+    let irq_entry_cycles = get_cycles() as u64;
+    // trap-entry timestamp for `sys_task_info`'s `trap_overhead_us`; see
+    // `TotalTasks::record_trap_overhead`. This local survives an in-trap
+    // context switch (`suspend_current_and_run_next` below) the same way
+    // `cx` itself does — `__switch` only swaps kernel stacks and
+    // registers, so this stack frame (and everything on it) just pauses
+    // until a later trap brings this task, and this exact frame, back.
+    let trap_entry_us = get_time_us();
+    TOTAL_TASKS.begin_trap(trap_entry_us);
+    // everything from the task's last dispatch (or its last trap return)
+    // until right now was spent running its own code
+    TOTAL_TASKS.record_trap_enter();
+    // `fs` is hart-wide, not per-task, so attribute a `Dirty` reading to
+    // whichever task is running right now and reset it to `Clean`
+    // immediately — otherwise the next task scheduled onto this hart would
+    // inherit a stale `Dirty` it never earned
+    if sstatus::read().fs() == sstatus::FS::Dirty {
+        mark_fp_dirty_current();
+        unsafe {
+            sstatus::set_fs(sstatus::FS::Clean);
+        }
+    }
+    let scause = scause::read();
+    let stval = stval::read();
+    // a write past the bottom of the user stack doesn't actually fault in
+    // this kernel (see the doc comment on `user_stack_guard_range` for
+    // why), so the guard region is checked directly against `sp` on every
+    // trap instead, rather than as one more exception arm below — it needs
+    // to catch overflow regardless of what specific trap happened to bring
+    // us in here
+    // sampled here rather than only on a stack-growth fault, since this
+    // kernel never demand-pages a stack and so never actually takes one
+    // (see `TaskControlBlock::stack_low_water_sp`'s doc comment); every
+    // trap is as close as this kernel gets to "catching" the stack at a
+    // given depth
+    record_stack_watermark_current(cx.x[2]);
+    let (guard_start, guard_end) = user_stack_guard_range(get_current_task());
+    if cx.x[2] >= guard_start && cx.x[2] < guard_end {
+        TRAP_HISTOGRAM.stack_overflow.fetch_add(1, Ordering::Relaxed);
+        println!("[kernel] Stack overflow in application, kernel killed it.");
+        exit_current_and_run_next(-1);
+    } else if is_non_executable_mmap_addr(cx.sepc) {
+        TRAP_HISTOGRAM.non_executable_fetch.fetch_add(1, Ordering::Relaxed);
+        // there's no real MMU here to raise an instruction-fetch-permission
+        // fault on its own (see `TaskManager::is_non_executable_mmap_addr`),
+        // so `sepc` — wherever execution was about to resume from — is
+        // checked directly against the current task's non-executable
+        // `mmap` regions on every trap, the same proactive-check pattern
+        // used above for the stack guard
+        println!("[kernel] Instruction fetch from non-executable page in application, kernel killed it.");
+        exit_current_and_run_next(-1);
+    } else {
+        match scause.cause() {
+            Trap::Exception(Exception::UserEnvCall) => {
+                TRAP_HISTOGRAM.syscall.fetch_add(1, Ordering::Relaxed);
+                cx.sepc += 4;
+                // sys_sigreturn has to replace the whole trap frame, not just
+                // return an isize through a0 like every other syscall, so it's
+                // special-cased here rather than dispatched through `syscall`
+                if cx.x[17] == SYSCALL_SIGRETURN {
+                    sigreturn_current(cx);
+                } else {
+                    cx.x[10] = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]) as usize;
+                }
+            }
+            Trap::Exception(Exception::StoreFault)
+            | Trap::Exception(Exception::StorePageFault)
+            | Trap::Exception(Exception::LoadFault)
+            | Trap::Exception(Exception::LoadPageFault)
+            | Trap::Exception(Exception::InstructionPageFault) => {
+                TRAP_HISTOGRAM.page_fault.fetch_add(1, Ordering::Relaxed);
+                // deferred to `handle_pending_signal_current` below, so a
+                // task with a `SIGSEGV` handler installed gets a chance to
+                // recover instead of being killed outright; see
+                // `TaskManager::segfault_current`
+                segfault_current(stval);
+            }
+            Trap::Exception(Exception::IllegalInstruction) => {
+                TRAP_HISTOGRAM.illegal_instruction.fetch_add(1, Ordering::Relaxed);
+                println!(
+                    "[kernel] IllegalInstruction at sepc={:#x} in application, kernel killed it.",
+                    cx.sepc
+                );
+                exit_current_and_run_next(-SIGILL);
+            }
+            Trap::Exception(Exception::LoadMisaligned)
+            | Trap::Exception(Exception::StoreMisaligned) => {
+                TRAP_HISTOGRAM.misaligned.fetch_add(1, Ordering::Relaxed);
+                println!(
+                    "[kernel] Misaligned memory access at {:#x} in application, kernel killed it.",
+                    stval
+                );
+                exit_current_and_run_next(-SIGBUS);
+            }
+            Trap::Exception(Exception::Breakpoint) => {
+                TRAP_HISTOGRAM.breakpoint.fetch_add(1, Ordering::Relaxed);
+                // recoverable: an `ebreak` is a full 4-byte instruction in
+                // the non-compressed encoding (the only one this kernel's
+                // toolchain emits), so stepping past it is just advancing
+                // `sepc` the same way `UserEnvCall` already does for `ecall`
+                println!("[kernel] ebreak at sepc={:#x}, resuming.", cx.sepc);
+                cx.sepc += 4;
+            }
+            Trap::Interrupt(Interrupt::SupervisorTimer) => {
+                TRAP_HISTOGRAM.timer_interrupt.fetch_add(1, Ordering::Relaxed);
+                // fires `TICKS_PER_SEC` times a second on every hart, so this
+                // is `trace!`, not `println!` — anything noisier than that
+                // would drown out every other log line on a normal run
+                trace!("[timer] tick on hart {}", hart_id());
+                // re-arm before yielding the CPU so a slow handler can't delay
+                // the next tick
+                set_next_trigger();
+                crate::uart::poll();
+                crate::timer::publish_vdso_tick();
+                crate::timer::wake_expired_sleepers();
+                fire_expired_itimers();
+                check_cpu_limit_current();
+                // preempted against its will, unlike `sys_yield`'s own call
+                // to `suspend_current_and_run_next`; see `sys_getrusage`'s
+                // `ru_nivcsw`
+                TOTAL_TASKS.record_involuntary_switch();
+                if IRQ_LATENCY_STATS {
+                    record_irq_latency(get_cycles() as u64 - irq_entry_cycles);
+                }
+                suspend_current_and_run_next(SwitchCause::TimerPreempt);
+            }
+            _ => {
+                TRAP_HISTOGRAM.other.fetch_add(1, Ordering::Relaxed);
+                panic!(
+                    "Unsupported trap {:?}, stval = {:#x}!",
+                    scause.cause(),
+                    stval
+                );
+            }
+        }
+    }
+    // if kernel-mode execution during this trap grew far enough to stomp
+    // the canary seeded into this task's kernel stack guard region, that's
+    // a kernel stack overflow; see `kernel_stack_canary_intact`'s doc for
+    // why this is the one place this kernel can check for that, and why a
+    // distinct, loud panic here beats returning to user mode over whatever
+    // got silently corrupted
+    if !kernel_stack_canary_intact(get_current_task()) {
+        panic!(
+            "kernel stack overflow on task {}: guard region canary was overwritten",
+            get_current_task()
+        );
+    }
+    // deliver any signal that arrived for this task before it returns to
+    // user mode
+    handle_pending_signal_current(cx);
+    // credit whatever of this trap's span wasn't already credited to a
+    // syscall body as trap overhead, before the final `user_time`/
+    // `kernel_time` bookkeeping below
+    TOTAL_TASKS.record_trap_overhead(trap_entry_us);
+    // we're about to return to user code: everything since entering this
+    // handler (across any task switches, whenever it resumes) was kernel
+    // time spent on this task's behalf
+    TOTAL_TASKS.record_trap_leave();
+    cx
+}
+
+pub use context::TrapContext;
@@ -0,0 +1,208 @@
+//! Buffered console input with a cooked-mode line discipline
+//!
+//! This kernel has no PLIC driver, so there's no way to take a genuine
+//! UART receive interrupt. [`poll`] stands in for one: it's called once
+//! per timer tick (the one interrupt source already wired up) and checks
+//! whether the SBI console has a byte waiting.
+//!
+//! By default (cooked mode) a typed byte is echoed straight back out,
+//! `0x7f`/`0x08` (the two bytes a terminal is likely to send for
+//! backspace) erase the last unconsumed character of the in-progress
+//! line and emit `\b \b` so the erasure is visible, and nothing reaches
+//! [`INPUT_BUFFER`] — and so nothing wakes a blocked reader — until Enter
+//! completes the line. [`set_raw_mode`] switches a task over to raw mode,
+//! where every byte is handed to [`INPUT_BUFFER`] (and so to
+//! [`blocking_read_byte`]) immediately and unechoed, exactly as this
+//! module behaved before line discipline existed.
+
+use crate::sync::{UPSafeCell, WaitQueue};
+use crate::task::wake_task;
+use core::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+
+const INPUT_BUF_LEN: usize = 256;
+/// the longest line [`LineBuffer`] will buffer before a completing `\n`;
+/// long past this, a line editor would have wrapped the terminal anyway
+const LINE_BUF_LEN: usize = 256;
+
+struct InputBuffer {
+    data: [u8; INPUT_BUF_LEN],
+    head: usize,
+    len: usize,
+}
+
+impl InputBuffer {
+    fn push(&mut self, byte: u8) {
+        if self.len == INPUT_BUF_LEN {
+            // the poller must never block, so if nobody's been reading,
+            // drop the oldest byte to make room rather than stall
+            self.head = (self.head + 1) % INPUT_BUF_LEN;
+            self.len -= 1;
+        }
+        let tail = (self.head + self.len) % INPUT_BUF_LEN;
+        self.data[tail] = byte;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte = self.data[self.head];
+        self.head = (self.head + 1) % INPUT_BUF_LEN;
+        self.len -= 1;
+        Some(byte)
+    }
+}
+
+/// the not-yet-completed line cooked mode is still accumulating
+struct LineBuffer {
+    data: [u8; LINE_BUF_LEN],
+    len: usize,
+}
+
+impl LineBuffer {
+    fn push(&mut self, byte: u8) -> bool {
+        if self.len == LINE_BUF_LEN {
+            return false;
+        }
+        self.data[self.len] = byte;
+        self.len += 1;
+        true
+    }
+
+    /// drop the last buffered byte, for backspace; `false` if the line
+    /// was already empty
+    fn backspace(&mut self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        self.len -= 1;
+        true
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = u8> + '_ {
+        let taken = self.len;
+        self.len = 0;
+        self.data[..taken].iter().copied()
+    }
+}
+
+lazy_static! {
+    static ref INPUT_BUFFER: UPSafeCell<InputBuffer> = unsafe {
+        UPSafeCell::new(InputBuffer {
+            data: [0; INPUT_BUF_LEN],
+            head: 0,
+            len: 0,
+        })
+    };
+    static ref PARTIAL_LINE: UPSafeCell<LineBuffer> = unsafe {
+        UPSafeCell::new(LineBuffer {
+            data: [0; LINE_BUF_LEN],
+            len: 0,
+        })
+    };
+    static ref READ_WAITERS: WaitQueue = WaitQueue::new();
+}
+
+/// whether the console is currently in raw mode; see this module's doc
+/// comment. Cooked (`false`) is the default, same as a freshly opened
+/// real tty.
+static RAW_MODE: AtomicBool = AtomicBool::new(false);
+
+/// switch the console between cooked (line-buffered, echoing) and raw
+/// (byte-at-a-time, unechoed) mode
+pub fn set_raw_mode(raw: bool) {
+    RAW_MODE.store(raw, Ordering::Relaxed);
+}
+
+/// backspace, as sent by most terminals (DEL, `0x7f`) or occasionally
+/// (`^H`, `0x08`)
+const BACKSPACE_BYTES: [u8; 2] = [0x7f, 0x08];
+
+/// feed one typed byte through the cooked-mode line discipline: echo it,
+/// handle backspace, and release the buffered line to [`INPUT_BUFFER`]
+/// once `\r` or `\n` completes it. Returns whether a byte actually became
+/// available to read — false for every ordinary keystroke and backspace,
+/// true only once Enter completes a line.
+///
+/// A test feeding `"ab\x7fc\n"` byte-by-byte through [`poll`] and then
+/// reading back `"ac"` via [`blocking_read_byte`] would be pure
+/// kernel-internal logic — no dependency on the sibling `user` crate,
+/// since [`poll`] and [`blocking_read_byte`] are both plain kernel
+/// functions this file could call directly. But this crate is built
+/// `#![no_std]`/`#![no_main]` for a bare-metal target with no host test
+/// harness wired up anywhere in this source tree (no `[[test]]` target,
+/// and no way to fake an SBI console byte arriving without a real one),
+/// so there's nothing to add such a test to.
+fn cook_byte(byte: u8) -> bool {
+    if BACKSPACE_BYTES.contains(&byte) {
+        if PARTIAL_LINE.exclusive_access().backspace() {
+            print!("\u{8} \u{8}");
+        }
+        return false;
+    }
+    if byte == b'\r' || byte == b'\n' {
+        println!();
+        let mut input = INPUT_BUFFER.exclusive_access();
+        for b in PARTIAL_LINE.exclusive_access().drain() {
+            input.push(b);
+        }
+        input.push(b'\n');
+        return true;
+    }
+    if PARTIAL_LINE.exclusive_access().push(byte) {
+        print!("{}", byte as char);
+    }
+    false
+}
+
+/// check the console for a waiting byte; if there is one, run it through
+/// the line discipline (see this module's doc comment) and, if that made
+/// a byte available to read, wake the oldest task blocked in
+/// [`blocking_read_byte`]
+pub fn poll() {
+    let ch = crate::sbi::console_getchar();
+    if ch == usize::MAX {
+        return;
+    }
+    let byte = ch as u8;
+    let readable = if RAW_MODE.load(Ordering::Relaxed) {
+        INPUT_BUFFER.exclusive_access().push(byte);
+        true
+    } else {
+        cook_byte(byte)
+    };
+    if readable {
+        if let Some(id) = READ_WAITERS.wake_one() {
+            wake_task(id);
+        }
+    }
+}
+
+/// read one byte from the console, blocking the calling task until one is
+/// available; returns `None` instead if a signal is delivered while
+/// blocked
+///
+/// See [`WaitQueue::sleep_current_interruptible`] for why this no longer
+/// hand-rolls its own add-then-block loop: the same check-join-recheck
+/// sequence it does is what keeps a byte that `poll` pushes between this
+/// function's check and it actually joining `READ_WAITERS` from being
+/// lost.
+pub fn blocking_read_byte() -> Option<u8> {
+    READ_WAITERS.sleep_current_interruptible(|| INPUT_BUFFER.exclusive_access().pop())
+}
+
+/// whether [`blocking_read_byte`] would return immediately right now,
+/// without blocking; used by `sys_poll`
+pub fn is_readable() -> bool {
+    INPUT_BUFFER.exclusive_access().len > 0
+}
+
+/// join [`READ_WAITERS`] so `id` is woken once a byte becomes available,
+/// without blocking it yet; used by `sys_poll` to join this queue alongside
+/// any pipes being polled in the same call, before a single
+/// `block_current_and_run_next`
+pub fn add_read_waiter(id: usize) {
+    READ_WAITERS.add(id);
+}